@@ -0,0 +1,69 @@
+//! Lists the files of the `assets` and `config` directories for the `embedded-assets` feature,
+//! so that every asset and config default is compiled into the binary without naming each one.
+
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+
+fn main() -> io::Result<()> {
+    if env::var_os("CARGO_FEATURE_EMBEDDED_ASSETS").is_none() {
+        return Ok(());
+    }
+
+    let manifest_dir =
+        PathBuf::from(env::var_os("CARGO_MANIFEST_DIR").expect("CARGO_MANIFEST_DIR"));
+    let out_dir = PathBuf::from(env::var_os("OUT_DIR").expect("OUT_DIR"));
+
+    embed_dir(
+        &manifest_dir.join("assets"),
+        "include_bytes",
+        &out_dir.join("embedded_assets.rs"),
+    )?;
+    embed_dir(
+        &manifest_dir.join("config"),
+        "include_str",
+        &out_dir.join("embedded_config.rs"),
+    )?;
+    Ok(())
+}
+
+/// Writes to `out` a slice of every file in `dir` keyed by its path relative to `dir`, each
+/// included with the `include` macro.
+fn embed_dir(dir: &Path, include: &str, out: &Path) -> io::Result<()> {
+    // Cargo reruns the script when any file in the directory changes.
+    println!("cargo:rerun-if-changed={}", dir.display());
+
+    let mut files = Vec::new();
+    list_files(dir, &mut files)?;
+    files.sort();
+
+    let entries = files
+        .iter()
+        .map(|path| {
+            let key = path
+                .strip_prefix(dir)
+                .expect("listed files are in the directory")
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            format!("    ({:?}, {}!({:?})),\n", key, include, path)
+        })
+        .collect::<String>();
+    fs::write(out, format!("&[\n{}]\n", entries))
+}
+
+/// Adds the files in `dir` and its subdirectories to `files`. User override files are left out,
+/// as they belong to whoever built the game.
+fn list_files(dir: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            list_files(&path, files)?;
+        } else if !path.to_string_lossy().ends_with(".user.ron") {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
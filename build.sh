@@ -27,8 +27,9 @@ RUSTFLAGS='-C target-feature=+atomics,+bulk-memory' \
 wasm-bindgen target/wasm32-unknown-unknown/debug/pong_wasm.wasm \
   --out-dir pkg --no-modules
 
-# worker.js crashes because it does not have `AudioContext` / `webkitAudioContext` in scope.
-# This prevents it from crashing.
+# Audio is played through an `AudioContext` on the main thread, but worker.js imports the same
+# generated JS and crashes because it does not have `AudioContext` / `webkitAudioContext` in scope.
+# This makes the lookup fall back to `null` in workers, where no audio is played.
 audio_context_workaround="const lAudioContext = (typeof AudioContext !== 'undefined' ? AudioContext : typeof webkitAudioContext !== 'undefined' ? webkitAudioContext : null)"
 
 sed -i "s/const lAudioContext.\+\$/${audio_context_workaround}/" 'pkg/pong_wasm.js'
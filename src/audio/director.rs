@@ -0,0 +1,371 @@
+use amethyst::{audio::SourceHandle, core::timing::Time};
+
+use super::{Music, MusicCue};
+use crate::ScoreBoard;
+
+/// Seconds a track takes to fade in or out when the music changes.
+const CROSSFADE_SECONDS: f32 = 2.0;
+/// Gain of the music while the game is paused.
+const PAUSED_GAIN: f32 = 0.3;
+/// Gain of the music while a score sound plays.
+const DUCK_GAIN: f32 = 0.35;
+/// Seconds the music stays ducked after a score.
+const DUCK_SECONDS: f32 = 1.0;
+/// Seconds the music takes to duck or recover.
+const DUCK_FADE_SECONDS: f32 = 0.15;
+
+/// A music track playing on an audio backend.
+pub trait MusicTrack {
+    /// Sets the volume of the track, from `0.0` to `1.0`.
+    fn set_volume(&mut self, volume: f32);
+    /// Returns whether the track has played to the end.
+    fn is_finished(&self) -> bool;
+}
+
+/// Result of starting a track on an audio backend.
+pub enum TrackStart<T> {
+    /// The track is playing, silent until it fades in.
+    Playing(T),
+    /// The track is not ready to play yet. It is started again on the next update.
+    Loading,
+    /// The track cannot be played. The next track is picked instead.
+    Failed,
+}
+
+/// Returns the cue the music follows, and whether the game is paused.
+pub fn music_cue(score_board: &ScoreBoard, time: &Time) -> (MusicCue, bool) {
+    let paused = time.time_scale() == 0.0;
+    if score_board.winner().is_some() {
+        (MusicCue::GameOver, false)
+    } else if score_board.is_match_point() {
+        (MusicCue::Intense, paused)
+    } else {
+        (MusicCue::Calm, paused)
+    }
+}
+
+/// A track with the cue it was picked for and its fade.
+struct Deck<T> {
+    track: T,
+    cue: MusicCue,
+    gain: f32,
+}
+
+/// Picks the music for the state of the match, crossfading between tracks and ducking the music
+/// under score sounds.
+pub struct MusicDirector<T, H = SourceHandle> {
+    /// The track that is playing, fading in if needed.
+    current: Option<Deck<T>>,
+    /// Tracks fading out.
+    fading: Vec<Deck<T>>,
+    /// Next track to play, waiting for the backend to be ready.
+    pending: Option<(MusicCue, H)>,
+    /// Seconds left before the music recovers from ducking.
+    duck_remaining: f32,
+    duck_gain: f32,
+}
+
+impl<T, H> Default for MusicDirector<T, H> {
+    fn default() -> Self {
+        MusicDirector {
+            current: None,
+            fading: Vec::new(),
+            pending: None,
+            duck_remaining: 0.0,
+            duck_gain: 1.0,
+        }
+    }
+}
+
+impl<T: MusicTrack, H: Clone> MusicDirector<T, H> {
+    /// Lowers the music for a moment, so that a sound stands out.
+    pub fn duck(&mut self) {
+        self.duck_remaining = DUCK_SECONDS;
+    }
+
+    /// Switches tracks if needed and updates the fades.
+    ///
+    /// `dt` is in real seconds, so that the music keeps fading while the game is paused.
+    pub fn update<F>(
+        &mut self,
+        music: &mut Music<H>,
+        (cue, paused): (MusicCue, bool),
+        volume: f32,
+        dt: f32,
+        mut start: F,
+    ) where
+        F: FnMut(&H) -> TrackStart<T>,
+    {
+        let cue = music.resolve(cue);
+
+        // Switch tracks when the mood changes or the track is over.
+        let finished = self
+            .current
+            .as_ref()
+            .map_or(true, |deck| deck.cue != cue || deck.track.is_finished());
+        if finished {
+            self.fading.extend(self.current.take());
+            if self
+                .pending
+                .as_ref()
+                .map_or(true, |(pending, _)| *pending != cue)
+            {
+                self.pending = music
+                    .tracks_mut(cue)
+                    .and_then(|tracks| tracks.next())
+                    .map(|handle| (cue, handle));
+            }
+        }
+        if let Some((cue, handle)) = self.pending.take() {
+            match start(&handle) {
+                TrackStart::Playing(track) => {
+                    self.current = Some(Deck {
+                        track,
+                        cue,
+                        gain: 0.0,
+                    })
+                }
+                TrackStart::Loading => self.pending = Some((cue, handle)),
+                TrackStart::Failed => {}
+            }
+        }
+
+        self.duck_remaining = (self.duck_remaining - dt).max(0.0);
+        let duck_target = if self.duck_remaining > 0.0 {
+            DUCK_GAIN
+        } else {
+            1.0
+        };
+        self.duck_gain = approach(self.duck_gain, duck_target, dt / DUCK_FADE_SECONDS);
+
+        let volume = self.duck_gain * volume;
+        let fade_step = dt / CROSSFADE_SECONDS;
+        if let Some(deck) = self.current.as_mut() {
+            let target = if paused { PAUSED_GAIN } else { 1.0 };
+            deck.gain = approach(deck.gain, target, fade_step);
+            deck.track.set_volume(deck.gain * volume);
+        }
+        self.fading
+            .retain(|deck| deck.gain > 0.0 && !deck.track.is_finished());
+        for deck in &mut self.fading {
+            deck.gain = approach(deck.gain, 0.0, fade_step);
+            deck.track.set_volume(deck.gain * volume);
+        }
+    }
+}
+
+/// Moves `value` towards `target` by at most `step`.
+fn approach(value: f32, target: f32, step: f32) -> f32 {
+    if value < target {
+        (value + step).min(target)
+    } else {
+        (value - step).max(target)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::{RepeatMode, TrackList};
+    use std::{cell::RefCell, rc::Rc};
+
+    /// Track that remembers the volume it was last set to.
+    #[derive(Clone, Default)]
+    struct FakeTrack(Rc<RefCell<(f32, bool)>>);
+
+    impl FakeTrack {
+        fn volume(&self) -> f32 {
+            self.0.borrow().0
+        }
+
+        fn finish(&self) {
+            self.0.borrow_mut().1 = true;
+        }
+    }
+
+    impl MusicTrack for FakeTrack {
+        fn set_volume(&mut self, volume: f32) {
+            self.0.borrow_mut().0 = volume;
+        }
+
+        fn is_finished(&self) -> bool {
+            self.0.borrow().1
+        }
+    }
+
+    /// Director playing music with one track per cue, named after the cue.
+    struct Player {
+        director: MusicDirector<FakeTrack, &'static str>,
+        music: Music<&'static str>,
+        /// Every track started, in order.
+        started: Vec<(&'static str, FakeTrack)>,
+    }
+
+    impl Player {
+        fn new(intense_tracks: &[&'static str]) -> Self {
+            let tracks =
+                |names: &[&'static str]| TrackList::new(names.to_vec(), false, RepeatMode::All);
+            Player {
+                director: MusicDirector::default(),
+                music: Music {
+                    calm: tracks(&["calm"]),
+                    intense: tracks(intense_tracks),
+                    game_over: tracks(&["game over"]),
+                },
+                started: Vec::new(),
+            }
+        }
+
+        fn update(&mut self, cue: MusicCue, paused: bool, dt: f32) {
+            let started = &mut self.started;
+            self.director
+                .update(&mut self.music, (cue, paused), 1.0, dt, |name| {
+                    let track = FakeTrack::default();
+                    started.push((*name, track.clone()));
+                    TrackStart::Playing(track)
+                });
+        }
+
+        fn names(&self) -> Vec<&'static str> {
+            self.started.iter().map(|(name, _)| *name).collect()
+        }
+
+        /// Returns the volume of the last track started.
+        fn volume(&self) -> f32 {
+            self.started.last().map_or(0.0, |(_, track)| track.volume())
+        }
+    }
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!(
+            (actual - expected).abs() < 1e-5,
+            "expected {}, got {}",
+            expected,
+            actual
+        );
+    }
+
+    fn score_board(score_left: i32, score_right: i32) -> ScoreBoard {
+        ScoreBoard {
+            score_left,
+            score_right,
+            ..ScoreBoard::new(Some(11))
+        }
+    }
+
+    #[test]
+    fn music_cue_follows_the_score_and_the_pause() {
+        let mut time = Time::default();
+        assert_eq!(
+            music_cue(&score_board(3, 4), &time),
+            (MusicCue::Calm, false)
+        );
+        assert_eq!(
+            music_cue(&score_board(10, 4), &time),
+            (MusicCue::Intense, false)
+        );
+
+        time.set_time_scale(0.0);
+        assert_eq!(music_cue(&score_board(3, 4), &time), (MusicCue::Calm, true));
+        assert_eq!(
+            music_cue(&score_board(4, 11), &time),
+            (MusicCue::GameOver, false)
+        );
+    }
+
+    #[test]
+    fn plays_the_track_of_each_cue() {
+        let mut player = Player::new(&["intense"]);
+        player.update(MusicCue::Calm, false, 0.1);
+        player.update(MusicCue::Calm, false, 0.1);
+        player.update(MusicCue::Intense, false, 0.1);
+        player.update(MusicCue::GameOver, false, 0.1);
+
+        assert_eq!(player.names(), vec!["calm", "intense", "game over"]);
+    }
+
+    #[test]
+    fn intense_cue_keeps_the_calm_track_without_intense_tracks() {
+        let mut player = Player::new(&[]);
+        player.update(MusicCue::Calm, false, 0.1);
+        player.update(MusicCue::Intense, false, 0.1);
+
+        assert_eq!(player.names(), vec!["calm"]);
+    }
+
+    #[test]
+    fn plays_the_next_track_when_one_finishes() {
+        let mut player = Player::new(&[]);
+        player.update(MusicCue::Calm, false, 0.1);
+        player.started[0].1.finish();
+        player.update(MusicCue::Calm, false, 0.1);
+
+        assert_eq!(player.names(), vec!["calm", "calm"]);
+    }
+
+    #[test]
+    fn waits_for_a_loading_track() {
+        let mut player = Player::new(&[]);
+        let mut attempts = 0;
+        for _ in 0..3 {
+            player
+                .director
+                .update(&mut player.music, (MusicCue::Calm, false), 1.0, 0.1, |_| {
+                    attempts += 1;
+                    if attempts < 3 {
+                        TrackStart::Loading
+                    } else {
+                        TrackStart::Playing(FakeTrack::default())
+                    }
+                });
+        }
+        assert_eq!(attempts, 3);
+
+        // The track is playing, so it is not started again.
+        player.update(MusicCue::Calm, false, 0.1);
+        assert!(player.started.is_empty());
+    }
+
+    #[test]
+    fn crossfades_between_tracks() {
+        let mut player = Player::new(&["intense"]);
+        player.update(MusicCue::Calm, false, 1.0);
+        assert_close(player.volume(), 0.5);
+        player.update(MusicCue::Calm, false, 1.0);
+        assert_close(player.volume(), 1.0);
+
+        player.update(MusicCue::Intense, false, 1.0);
+        assert_close(player.started[0].1.volume(), 0.5);
+        assert_close(player.volume(), 0.5);
+
+        player.update(MusicCue::Intense, false, 1.0);
+        assert_close(player.started[0].1.volume(), 0.0);
+        assert_close(player.volume(), 1.0);
+    }
+
+    #[test]
+    fn pausing_lowers_the_music() {
+        let mut player = Player::new(&[]);
+        player.update(MusicCue::Calm, false, CROSSFADE_SECONDS);
+        assert_close(player.volume(), 1.0);
+
+        player.update(MusicCue::Calm, true, CROSSFADE_SECONDS);
+        assert_close(player.volume(), PAUSED_GAIN);
+
+        player.update(MusicCue::Calm, false, CROSSFADE_SECONDS);
+        assert_close(player.volume(), 1.0);
+    }
+
+    #[test]
+    fn ducks_the_music_then_recovers() {
+        let mut player = Player::new(&[]);
+        player.update(MusicCue::Calm, false, CROSSFADE_SECONDS);
+
+        player.director.duck();
+        player.update(MusicCue::Calm, false, DUCK_FADE_SECONDS);
+        assert_close(player.volume(), DUCK_GAIN);
+
+        player.update(MusicCue::Calm, false, DUCK_SECONDS);
+        assert_close(player.volume(), 1.0);
+    }
+}
@@ -0,0 +1,185 @@
+use amethyst::{
+    assets::{AssetStorage, Loader},
+    audio::{AudioData, Source, SourceHandle},
+};
+use rand::{seq::SliceRandom, thread_rng};
+use rodio::{
+    buffer::SamplesBuffer, decoder::DecoderError, source::ChannelVolume, Decoder, Source as _,
+};
+use std::{cmp::Ordering, collections::HashMap, f32::consts::FRAC_PI_4, io::Cursor};
+
+use super::{synth::wav_bytes, Sounds};
+use crate::{
+    event::{PongEvent, PongEventKind, Surface},
+    game_config::GameConfig,
+};
+
+/// Stereo positions impact sounds are rendered at, from left to right.
+const PANS: [f32; 5] = [-1.0, -0.5, 0.0, 0.5, 1.0];
+/// Playback speeds impact sounds are rendered at.
+const PITCHES: [f32; 5] = [0.8, 0.9, 1.0, 1.1, 1.2];
+
+/// How a single impact sound is played.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Impact {
+    /// Stereo position, from `-1.0` (left) to `1.0` (right).
+    pub pan: f32,
+    /// Playback speed multiplier, which also raises or lowers the pitch.
+    pub pitch: f32,
+    /// Volume multiplier, from `0.0` to `1.0`.
+    pub volume: f32,
+}
+
+impl Impact {
+    /// Returns how to play the sound of the ball hitting `surface` at `x` with the given speed.
+    pub fn new(x: f32, speed: f32, surface: Surface, game_config: &GameConfig) -> Self {
+        let pan = pan(x, game_config);
+
+        // Compare against the serve speed towards the same kind of surface.
+        let [serve_x, serve_y] = game_config.ball.velocity;
+        let reference_speed = if surface.is_paddle() {
+            serve_x.abs()
+        } else {
+            serve_y.abs()
+        };
+        let intensity = (speed / reference_speed).max(0.0).min(2.0);
+
+        // Paddles ring a little higher than the walls.
+        let base_pitch = if surface.is_paddle() { 1.1 } else { 0.9 };
+        let pitch = base_pitch * (0.85 + 0.15 * intensity);
+        let volume = (0.6 + 0.4 * intensity).min(1.0);
+
+        Impact { pan, pitch, volume }
+    }
+
+    /// Returns how to play the sound of a goal going in at `x`, towards the goal.
+    pub fn goal(x: f32, game_config: &GameConfig) -> Self {
+        Impact {
+            pan: pan(x, game_config),
+            pitch: 1.0,
+            volume: 1.0,
+        }
+    }
+
+    /// Returns how to play the sound of the event, for events with a place in the arena.
+    pub fn of(event: &PongEvent, game_config: &GameConfig) -> Option<Self> {
+        match *event {
+            PongEvent::Bounce {
+                position,
+                speed,
+                surface,
+                ..
+            } => Some(Impact::new(position[0], speed, surface, game_config)),
+            PongEvent::Score { position, .. } => Some(Impact::goal(position[0], game_config)),
+            PongEvent::Serve { .. } | PongEvent::GameOver { .. } => None,
+        }
+    }
+}
+
+/// Returns the stereo position of `x` in the arena.
+fn pan(x: f32, game_config: &GameConfig) -> f32 {
+    ((x / game_config.arena.width) * 2.0 - 1.0)
+        .max(-1.0)
+        .min(1.0)
+}
+
+/// Returns the index of the step closest to `value`.
+fn nearest(steps: &[f32], value: f32) -> usize {
+    (0..steps.len())
+        .min_by(|&a, &b| {
+            let distance = |i: usize| (steps[i] - value).abs();
+            distance(a)
+                .partial_cmp(&distance(b))
+                .unwrap_or(Ordering::Equal)
+        })
+        .unwrap_or(0)
+}
+
+/// A sound rendered at every pan in `PANS` and pitch in `PITCHES`, so that it is decoded once
+/// rather than on every impact, and played through the `Output` like any other sound.
+pub struct ImpactSound {
+    /// Indexed by pan, then by pitch.
+    variants: Vec<SourceHandle>,
+}
+
+impl ImpactSound {
+    /// Decodes the sound and loads a variant of it for each pan and pitch.
+    pub fn load(
+        source: &Source,
+        loader: &Loader,
+        storage: &AssetStorage<Source>,
+    ) -> Result<Self, DecoderError> {
+        let decoder = Decoder::new(Cursor::new(source.clone()))?;
+        let (channels, sample_rate) = (decoder.channels(), decoder.sample_rate());
+        let samples = decoder.convert_samples::<f32>().collect::<Vec<_>>();
+
+        let variants = PANS
+            .iter()
+            .flat_map(|&pan| {
+                // Equal power panning keeps the loudness steady across the arena.
+                let angle = (pan + 1.0) * FRAC_PI_4;
+                let panned = ChannelVolume::new(
+                    SamplesBuffer::new(channels, sample_rate, samples.clone()),
+                    vec![angle.cos(), angle.sin()],
+                )
+                .convert_samples::<i16>()
+                .collect::<Vec<_>>();
+
+                // Playing the samples faster raises the pitch, as `Source::speed` would.
+                PITCHES
+                    .iter()
+                    .map(|&pitch| {
+                        let rate = (sample_rate as f32 * pitch).round() as u32;
+                        loader.load_from_data(AudioData(wav_bytes(&panned, 2, rate)), (), storage)
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        Ok(ImpactSound { variants })
+    }
+
+    /// Returns the variant closest to how the impact is played.
+    pub fn variant(&self, impact: Impact) -> &SourceHandle {
+        let index = nearest(&PANS, impact.pan) * PITCHES.len() + nearest(&PITCHES, impact.pitch);
+        &self.variants[index]
+    }
+}
+
+/// Impact sounds for the events with a place in the arena.
+#[derive(Default)]
+pub struct ImpactSounds {
+    sounds: HashMap<PongEventKind, Vec<ImpactSound>>,
+}
+
+impl ImpactSounds {
+    /// Renders the loaded bounce and goal sounds. Sounds that failed to load are left out.
+    pub fn load(sounds: &Sounds, loader: &Loader, storage: &AssetStorage<Source>) -> Self {
+        let sounds = [PongEventKind::Bounce, PongEventKind::Score]
+            .iter()
+            .map(|&kind| {
+                let impacts = sounds
+                    .effects
+                    .get(&kind)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|handle| storage.get(handle))
+                    .filter_map(|source| {
+                        ImpactSound::load(source, loader, storage)
+                            .map_err(|e| log::error!("Failed to decode impact sound: {}", e))
+                            .ok()
+                    })
+                    .collect();
+                (kind, impacts)
+            })
+            .collect();
+        ImpactSounds { sounds }
+    }
+
+    /// Returns one of the sounds for the event kind, picked at random.
+    pub fn pick(&self, kind: PongEventKind) -> Option<&ImpactSound> {
+        self.sounds
+            .get(&kind)
+            .and_then(|sounds| sounds.choose(&mut thread_rng()))
+    }
+}
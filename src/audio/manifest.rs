@@ -0,0 +1,64 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::event::PongEventKind;
+
+/// Music and sound effects to load, read from `audio/audio.ron` in the assets directory.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AudioManifest {
+    /// Background music.
+    pub music: Playlist,
+    /// Music played near match point, in place of `music`. Falls back to `music` when empty.
+    pub intense_music: Playlist,
+    /// Music played on the game over screen. The music fades out when empty.
+    pub game_over_music: Playlist,
+    /// Where the sound effects come from.
+    pub effects_source: EffectsSource,
+    /// Sound files for each event. One of the files is picked at random each time.
+    pub sounds: HashMap<PongEventKind, Vec<String>>,
+}
+
+/// Where the sound effects come from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum EffectsSource {
+    /// The files listed in `sounds`.
+    Files,
+    /// Blips synthesized at startup, needing no audio files.
+    Synthesized,
+}
+
+impl Default for EffectsSource {
+    fn default() -> Self {
+        EffectsSource::Files
+    }
+}
+
+/// Tracks played as background music.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Playlist {
+    /// Paths of the tracks, relative to the assets directory.
+    pub tracks: Vec<String>,
+    /// Whether the tracks are shuffled.
+    pub shuffle: bool,
+    /// What happens when a track finishes.
+    pub repeat: RepeatMode,
+}
+
+/// What happens when a track finishes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum RepeatMode {
+    /// Stop after the last track.
+    Off,
+    /// Play the same track again.
+    One,
+    /// Start again from the first track, reshuffling if shuffle is on.
+    All,
+}
+
+impl Default for RepeatMode {
+    fn default() -> Self {
+        RepeatMode::All
+    }
+}
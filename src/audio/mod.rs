@@ -0,0 +1,317 @@
+mod director;
+mod impact;
+mod manifest;
+mod settings;
+mod synth;
+#[cfg(feature = "wasm")]
+mod web;
+
+#[cfg(feature = "wasm")]
+pub use self::web::{Decoded, WebAudio, WebTrack};
+pub use self::{
+    director::{music_cue, MusicDirector, MusicTrack, TrackStart},
+    impact::{Impact, ImpactSounds},
+    manifest::{AudioManifest, EffectsSource, Playlist, RepeatMode},
+    settings::{step_volume, AudioSettings, AUDIO_SETTINGS_PATH, VOLUME_STEP},
+};
+
+use amethyst::{
+    assets::{AssetStorage, Loader, ProgressCounter, Source as AssetSource},
+    audio::{output::Output, OggFormat, Source, SourceHandle, WavFormat},
+    ecs::{World, WorldExt},
+};
+use rand::{seq::SliceRandom, thread_rng};
+use std::{collections::HashMap, path::Path};
+
+use crate::{
+    error::PongError,
+    event::{PongEvent, PongEventKind},
+    game_config::GameConfig,
+};
+
+/// Sound effects for each kind of event.
+#[derive(Default)]
+pub struct Sounds {
+    effects: HashMap<PongEventKind, Vec<SourceHandle>>,
+}
+
+impl Sounds {
+    /// Returns one of the sounds for the event kind, picked at random.
+    pub fn pick(&self, kind: PongEventKind) -> Option<&SourceHandle> {
+        self.effects
+            .get(&kind)
+            .and_then(|sounds| sounds.choose(&mut thread_rng()))
+    }
+
+    /// Returns every sound, of every event kind.
+    #[cfg(feature = "wasm")]
+    pub fn all(&self) -> impl Iterator<Item = &SourceHandle> {
+        self.effects.values().flatten()
+    }
+}
+
+/// Background music for each mood of the game.
+pub struct Music<H = SourceHandle> {
+    /// Played during a rally.
+    pub calm: TrackList<H>,
+    /// Played near match point.
+    pub intense: TrackList<H>,
+    /// Played on the game over screen.
+    pub game_over: TrackList<H>,
+}
+
+/// The mood the music follows.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MusicCue {
+    Calm,
+    Intense,
+    GameOver,
+}
+
+impl<H: Clone> Music<H> {
+    /// Returns the tracks played for a cue, `None` when there are no tracks to play.
+    pub fn tracks_mut(&mut self, cue: MusicCue) -> Option<&mut TrackList<H>> {
+        let tracks = match self.resolve(cue) {
+            MusicCue::Calm => &mut self.calm,
+            MusicCue::Intense => &mut self.intense,
+            MusicCue::GameOver => &mut self.game_over,
+        };
+        if tracks.is_empty() {
+            None
+        } else {
+            Some(tracks)
+        }
+    }
+
+    /// Returns the cue whose tracks are played for `cue`. Intense moments fall back to the calm
+    /// tracks.
+    pub fn resolve(&self, cue: MusicCue) -> MusicCue {
+        match cue {
+            MusicCue::Intense if self.intense.is_empty() => MusicCue::Calm,
+            cue => cue,
+        }
+    }
+}
+
+/// Music tracks, in the order they are played.
+pub struct TrackList<H = SourceHandle> {
+    tracks: Vec<H>,
+    shuffle: bool,
+    repeat: RepeatMode,
+    /// Indices into `tracks` for the current run through the playlist.
+    order: Vec<usize>,
+    /// Position in `order` of the track that is playing.
+    position: Option<usize>,
+}
+
+impl<H: Clone> TrackList<H> {
+    pub fn new(tracks: Vec<H>, shuffle: bool, repeat: RepeatMode) -> Self {
+        let mut music = TrackList {
+            tracks,
+            shuffle,
+            repeat,
+            order: Vec::new(),
+            position: None,
+        };
+        music.reorder();
+        music
+    }
+
+    /// Returns the next track to play, `None` when the playlist is over.
+    pub fn next(&mut self) -> Option<H> {
+        let position = match (self.position, self.repeat) {
+            (Some(position), RepeatMode::One) => position,
+            (Some(position), _) if position + 1 < self.order.len() => position + 1,
+            (Some(_), RepeatMode::Off) => return None,
+            (Some(_), RepeatMode::All) => {
+                self.reorder();
+                0
+            }
+            (None, _) => 0,
+        };
+        self.position = Some(position);
+
+        self.order
+            .get(position)
+            .and_then(|&index| self.tracks.get(index))
+            .cloned()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tracks.is_empty()
+    }
+
+    fn reorder(&mut self) {
+        self.order = (0..self.tracks.len()).collect();
+        if self.shuffle {
+            self.order.shuffle(&mut thread_rng());
+        }
+    }
+}
+
+/// Loads an audio file, picking the format from its extension.
+fn load_audio_track(
+    loader: &Loader,
+    world: &World,
+    file: &str,
+    progress: &mut ProgressCounter,
+) -> Option<SourceHandle> {
+    let extension = Path::new(file)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_lowercase);
+    match extension.as_deref() {
+        Some("ogg") => Some(loader.load(file, OggFormat, progress, &world.read_resource())),
+        Some("wav") => Some(loader.load(file, WavFormat, progress, &world.read_resource())),
+        _ => {
+            log::error!("Unsupported audio format for `{}`.", file);
+            None
+        }
+    }
+}
+
+/// Reads the audio manifest from the same place as the other assets.
+fn load_manifest(assets_dir: &Path) -> Result<AudioManifest, PongError> {
+    use crate::AUDIO_MANIFEST;
+
+    let error = |message: String| PongError::Asset {
+        path: AUDIO_MANIFEST.to_string(),
+        message,
+    };
+    let bytes = manifest_source(assets_dir)
+        .load(AUDIO_MANIFEST)
+        .map_err(|e| error(e.to_string()))?;
+    ron::de::from_bytes(&bytes).map_err(|e| error(e.to_string()))
+}
+
+#[cfg(not(feature = "embedded-assets"))]
+fn manifest_source(assets_dir: &Path) -> impl AssetSource {
+    amethyst::assets::Directory::new(assets_dir)
+}
+
+#[cfg(feature = "embedded-assets")]
+fn manifest_source(_assets_dir: &Path) -> impl AssetSource {
+    crate::embedded_assets::EmbeddedAssets
+}
+
+/// Initialise audio in the world. This includes the background track and the
+/// sound effects, whose loading is tracked by `progress`.
+///
+/// Returns the error reading the audio manifest, in which case there is no music and the sound
+/// effects are synthesized.
+pub fn initialise_audio(
+    world: &mut World,
+    assets_dir: &Path,
+    progress: &mut ProgressCounter,
+) -> Result<(), PongError> {
+    let (manifest, manifest_error) = match load_manifest(assets_dir) {
+        Ok(manifest) => (manifest, None),
+        Err(e) => {
+            log::error!("{}", e);
+            (AudioManifest::default(), Some(e))
+        }
+    };
+    // Without a manifest, there are no files to play effects from.
+    let effects_source = match manifest_error {
+        None => manifest.effects_source,
+        Some(_) => EffectsSource::Synthesized,
+    };
+
+    let (sound_effects, music) = {
+        let loader = world.read_resource::<Loader>();
+
+        let mut load_playlist = |playlist: &Playlist| {
+            let tracks = playlist
+                .tracks
+                .iter()
+                .filter_map(|file| load_audio_track(&loader, &world, file, progress))
+                .collect::<Vec<_>>();
+            TrackList::new(tracks, playlist.shuffle, playlist.repeat)
+        };
+        let music = Music {
+            calm: load_playlist(&manifest.music),
+            intense: load_playlist(&manifest.intense_music),
+            game_over: load_playlist(&manifest.game_over_music),
+        };
+
+        let effects = match effects_source {
+            EffectsSource::Files => manifest
+                .sounds
+                .iter()
+                .map(|(kind, files)| {
+                    let sounds = files
+                        .iter()
+                        .filter_map(|file| load_audio_track(&loader, &world, file, progress))
+                        .collect::<Vec<_>>();
+                    (*kind, sounds)
+                })
+                .collect(),
+            EffectsSource::Synthesized => synth::load_synthesized(&loader, &world.read_resource()),
+        };
+        let sound = Sounds { effects };
+
+        (sound, music)
+    };
+
+    // Add sound effects to the world. We have to do this in another scope because
+    // world won't let us insert new resources as long as `Loader` is borrowed.
+    world.insert(sound_effects);
+    world.insert(music);
+
+    match manifest_error {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
+}
+
+/// Decodes the loaded bounce and goal sounds once, rendering them at every pan and pitch they
+/// are played at.
+///
+/// There is no `Output` on the wasm build, which pans sounds through Web Audio instead.
+pub fn load_impact_sounds(world: &mut World) {
+    if world.try_fetch::<Output>().is_none() {
+        return;
+    }
+    let impact_sounds = match world.try_fetch::<Sounds>() {
+        Some(sounds) => ImpactSounds::load(&sounds, &world.read_resource(), &world.read_resource()),
+        None => return,
+    };
+    world.insert(impact_sounds);
+}
+
+/// Plays the sound for an event.
+///
+/// Bounces and goals play the variant of their `ImpactSounds` panned, and for bounces pitched,
+/// closest to where and how hard the ball hit. Other events, and impacts whose variants have not
+/// loaded yet, play centred.
+pub fn play_event(
+    event: &PongEvent,
+    sounds: &Sounds,
+    impact_sounds: Option<&ImpactSounds>,
+    storage: &AssetStorage<Source>,
+    output: Option<&Output>,
+    volume: f32,
+    game_config: &GameConfig,
+) {
+    let output = match output {
+        Some(output) => output,
+        None => return,
+    };
+
+    let impact = Impact::of(event, game_config).and_then(|impact| {
+        let variant = impact_sounds?.pick(event.kind())?.variant(impact);
+        storage
+            .get(variant)
+            .map(|sound| (sound, volume * impact.volume))
+    });
+    let played = impact.or_else(|| {
+        sounds
+            .pick(event.kind())
+            .and_then(|handle| storage.get(handle))
+            .map(|sound| (sound, volume))
+    });
+
+    if let Some((sound, volume)) = played {
+        output.play_once(sound, volume);
+    }
+}
@@ -0,0 +1,91 @@
+use serde::{Deserialize, Serialize};
+
+/// Location of the audio settings, relative to the application root.
+pub const AUDIO_SETTINGS_PATH: &str = "config/audio.ron";
+
+/// Amount a volume changes with each volume up or down action.
+pub const VOLUME_STEP: f32 = 0.1;
+
+/// Volumes of the music and sound effects.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AudioSettings {
+    /// Volume applied to all audio, from `0.0` to `1.0`.
+    pub master: f32,
+    /// Volume of the background music, from `0.0` to `1.0`.
+    pub music: f32,
+    /// Volume of the sound effects, from `0.0` to `1.0`.
+    pub effects: f32,
+    /// Whether all audio is muted.
+    pub muted: bool,
+    /// Whether the background music is muted.
+    pub music_muted: bool,
+    /// Whether the sound effects are muted.
+    pub effects_muted: bool,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        AudioSettings {
+            master: 1.0,
+            // Music is a bit loud, reduce the volume.
+            music: 0.25,
+            effects: 1.0,
+            muted: false,
+            music_muted: false,
+            effects_muted: false,
+        }
+    }
+}
+
+impl AudioSettings {
+    /// Returns the volume the background music is played at.
+    pub fn music_volume(&self) -> f32 {
+        if self.muted || self.music_muted {
+            0.0
+        } else {
+            self.master * self.music
+        }
+    }
+
+    /// Returns the volume the sound effects are played at.
+    pub fn effects_volume(&self) -> f32 {
+        if self.muted || self.effects_muted {
+            0.0
+        } else {
+            self.master * self.effects
+        }
+    }
+
+    /// Changes the master volume by `delta`, unmuting the audio.
+    pub fn adjust_master(&mut self, delta: f32) {
+        self.master = step_volume(self.master, delta);
+        self.muted = false;
+    }
+
+    /// Loads the audio settings saved in `localStorage`, falling back to the defaults. Natively
+    /// they are loaded with the other config files.
+    #[cfg(feature = "wasm")]
+    pub fn load() -> Self {
+        match crate::storage::load(AUDIO_SETTINGS_PATH) {
+            Ok(audio_settings) => audio_settings.unwrap_or_default(),
+            Err(e) => {
+                log::error!("Ignoring saved audio settings: {}", e);
+                AudioSettings::default()
+            }
+        }
+    }
+
+    /// Saves the audio settings.
+    pub fn save(&self) {
+        if let Err(e) = crate::storage::save(AUDIO_SETTINGS_PATH, self) {
+            log::error!("Failed to save audio settings: {}", e);
+        }
+    }
+}
+
+/// Returns the volume changed by `delta`, rounded to the nearest step and kept within range.
+pub fn step_volume(volume: f32, delta: f32) -> f32 {
+    let volume = ((volume + delta) / VOLUME_STEP).round() * VOLUME_STEP;
+    volume.max(0.0).min(1.0)
+}
@@ -0,0 +1,212 @@
+//! Square wave blips in the style of the original Pong, for builds without audio files.
+
+use amethyst::{
+    assets::{AssetStorage, Loader},
+    audio::{AudioData, Source, SourceHandle},
+};
+use std::collections::HashMap;
+
+use crate::event::PongEventKind;
+
+/// Samples per second of the generated sounds.
+pub const SAMPLE_RATE: u32 = 44_100;
+
+/// A sequence of square wave notes, each shaped by the same envelope.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Blip {
+    /// Frequency in hertz and duration in seconds of each note.
+    pub notes: Vec<(f32, f32)>,
+    /// Seconds each note takes to fade in.
+    pub attack: f32,
+    /// Seconds each note takes to fade out.
+    pub release: f32,
+    /// Peak amplitude, from `0.0` to `1.0`.
+    pub amplitude: f32,
+}
+
+impl Blip {
+    /// Returns the blip played for an event.
+    pub fn for_event(kind: PongEventKind) -> Self {
+        let notes = match kind {
+            PongEventKind::Bounce => vec![(459.0, 0.04)],
+            PongEventKind::Score => vec![(490.0, 0.25)],
+            PongEventKind::Serve => vec![(660.0, 0.05), (880.0, 0.05)],
+            PongEventKind::GameOver => {
+                vec![(523.0, 0.15), (392.0, 0.15), (330.0, 0.15), (262.0, 0.3)]
+            }
+        };
+
+        Blip {
+            notes,
+            attack: 0.002,
+            release: 0.01,
+            amplitude: 0.5,
+        }
+    }
+
+    /// Returns the 16 bit mono samples of the blip.
+    pub fn render(&self) -> Vec<i16> {
+        let sample_rate = SAMPLE_RATE as f32;
+        let peak = f32::from(std::i16::MAX) * self.amplitude.max(0.0).min(1.0);
+
+        self.notes
+            .iter()
+            .flat_map(|&(frequency, duration)| {
+                let sample_count = (duration * sample_rate).round() as usize;
+                (0..sample_count).map(move |index| {
+                    let t = index as f32 / sample_rate;
+                    let square = if (t * frequency).fract() < 0.5 {
+                        1.0
+                    } else {
+                        -1.0
+                    };
+                    (square * self.envelope(t, duration) * peak) as i16
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the gain at `t` seconds into a note lasting `duration` seconds.
+    fn envelope(&self, t: f32, duration: f32) -> f32 {
+        let fade_in = if self.attack > 0.0 {
+            (t / self.attack).min(1.0)
+        } else {
+            1.0
+        };
+        let fade_out = if self.release > 0.0 {
+            ((duration - t) / self.release).min(1.0)
+        } else {
+            1.0
+        };
+        fade_in.min(fade_out).max(0.0)
+    }
+}
+
+/// Returns the interleaved samples encoded as a 16 bit PCM WAV file.
+pub fn wav_bytes(samples: &[i16], channels: u16, sample_rate: u32) -> Vec<u8> {
+    const BITS_PER_SAMPLE: u16 = 16;
+    let block_align = channels * BITS_PER_SAMPLE / 8;
+    let byte_rate = sample_rate * u32::from(block_align);
+    let data_len = (samples.len() * 2) as u32;
+
+    let mut bytes = Vec::with_capacity(44 + data_len as usize);
+    bytes.extend_from_slice(b"RIFF");
+    bytes.extend_from_slice(&(36 + data_len).to_le_bytes());
+    bytes.extend_from_slice(b"WAVE");
+
+    bytes.extend_from_slice(b"fmt ");
+    bytes.extend_from_slice(&16u32.to_le_bytes());
+    bytes.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    bytes.extend_from_slice(&channels.to_le_bytes());
+    bytes.extend_from_slice(&sample_rate.to_le_bytes());
+    bytes.extend_from_slice(&byte_rate.to_le_bytes());
+    bytes.extend_from_slice(&block_align.to_le_bytes());
+    bytes.extend_from_slice(&BITS_PER_SAMPLE.to_le_bytes());
+
+    bytes.extend_from_slice(b"data");
+    bytes.extend_from_slice(&data_len.to_le_bytes());
+    samples
+        .iter()
+        .for_each(|sample| bytes.extend_from_slice(&sample.to_le_bytes()));
+
+    bytes
+}
+
+/// Synthesizes a sound effect for every event kind.
+pub fn load_synthesized(
+    loader: &Loader,
+    storage: &AssetStorage<Source>,
+) -> HashMap<PongEventKind, Vec<SourceHandle>> {
+    [
+        PongEventKind::Bounce,
+        PongEventKind::Score,
+        PongEventKind::Serve,
+        PongEventKind::GameOver,
+    ]
+    .iter()
+    .map(|&kind| {
+        let wav = wav_bytes(&Blip::for_event(kind).render(), 1, SAMPLE_RATE);
+        let handle = loader.load_from_data(AudioData(wav), (), storage);
+        (kind, vec![handle])
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn blip(notes: Vec<(f32, f32)>, amplitude: f32) -> Blip {
+        Blip {
+            notes,
+            attack: 0.002,
+            release: 0.01,
+            amplitude,
+        }
+    }
+
+    /// Returns the loudest sample.
+    fn peak(samples: &[i16]) -> i32 {
+        samples
+            .iter()
+            .map(|sample| i32::from(*sample).abs())
+            .max()
+            .unwrap()
+    }
+
+    #[test]
+    fn render_has_a_sample_per_tick_of_each_note() {
+        let samples = blip(vec![(440.0, 0.1), (880.0, 0.05)], 0.5).render();
+        assert_eq!(samples.len(), 4410 + 2205);
+    }
+
+    #[test]
+    fn render_peaks_at_the_amplitude() {
+        let samples = blip(vec![(440.0, 0.1)], 0.5).render();
+        assert_eq!(peak(&samples), i32::from(std::i16::MAX) / 2);
+    }
+
+    #[test]
+    fn render_clamps_the_amplitude() {
+        let samples = blip(vec![(440.0, 0.1)], 2.0).render();
+        assert_eq!(peak(&samples), i32::from(std::i16::MAX));
+    }
+
+    #[test]
+    fn render_fades_each_note_in_and_out() {
+        let samples = blip(vec![(440.0, 0.1)], 0.5).render();
+        assert_eq!(samples[0], 0);
+        assert!(i32::from(samples[samples.len() - 1]).abs() < peak(&samples) / 100);
+    }
+
+    #[test]
+    fn every_event_has_a_blip() {
+        for kind in &[
+            PongEventKind::Bounce,
+            PongEventKind::Score,
+            PongEventKind::Serve,
+            PongEventKind::GameOver,
+        ] {
+            assert!(!Blip::for_event(*kind).render().is_empty());
+        }
+    }
+
+    #[test]
+    fn wav_bytes_has_a_header_then_the_samples() {
+        let samples = [0, 1, -1, std::i16::MAX];
+        let bytes = wav_bytes(&samples, 2, 48_000);
+
+        assert_eq!(bytes.len(), 44 + samples.len() * 2);
+        assert_eq!(&bytes[0..4], b"RIFF");
+        assert_eq!(bytes[4..8], (36 + 8u32).to_le_bytes());
+        assert_eq!(&bytes[8..16], b"WAVEfmt ");
+        assert_eq!(bytes[22..24], 2u16.to_le_bytes());
+        assert_eq!(bytes[24..28], 48_000u32.to_le_bytes());
+        assert_eq!(bytes[28..32], (48_000u32 * 4).to_le_bytes());
+        assert_eq!(bytes[32..34], 4u16.to_le_bytes());
+        assert_eq!(&bytes[36..40], b"data");
+        assert_eq!(bytes[40..44], 8u32.to_le_bytes());
+        assert_eq!(bytes[44..46], 0i16.to_le_bytes());
+        assert_eq!(bytes[50..52], std::i16::MAX.to_le_bytes());
+    }
+}
@@ -0,0 +1,235 @@
+//! Audio output through the browser's Web Audio API.
+//!
+//! The game loop runs on the page's main thread, which is the only thread with an `AudioContext`,
+//! so everything here must be used from a thread local system.
+
+use amethyst::{
+    assets::AssetStorage,
+    audio::{Source, SourceHandle},
+};
+use js_sys::Uint8Array;
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use wasm_bindgen::{prelude::*, JsCast};
+use web_sys::{AudioBuffer, AudioBufferSourceNode, AudioContext, AudioNode, GainNode};
+
+use super::{Impact, MusicTrack, TrackStart};
+
+/// Decoding state of a sound.
+#[derive(Clone)]
+pub enum Decoded {
+    /// The sound is still loading or being decoded.
+    Decoding,
+    Ready(AudioBuffer),
+    Failed,
+}
+
+/// Callbacks of a `decodeAudioData` promise, for a decoded sound and for an error.
+type DecodeCallbacks = (Closure<dyn FnMut(JsValue)>, Closure<dyn FnMut(JsValue)>);
+
+/// An `AudioContext` and the sounds decoded for it.
+pub struct WebAudio {
+    context: AudioContext,
+    /// Decoded sounds by source handle id, filled in as the browser finishes decoding them.
+    buffers: Rc<RefCell<HashMap<u32, Decoded>>>,
+    /// Callbacks of the sounds being decoded, dropped once their promise has settled.
+    decode_callbacks: RefCell<HashMap<u32, DecodeCallbacks>>,
+    /// Listener that resumes the context on the first user gesture.
+    _on_gesture: Option<Closure<dyn FnMut()>>,
+}
+
+impl WebAudio {
+    /// Creates an audio context, which starts playing on the first user gesture.
+    ///
+    /// Returns `None` when the browser has no Web Audio support, in which case the game is silent.
+    pub fn new() -> Option<Self> {
+        let context = match AudioContext::new() {
+            Ok(context) => context,
+            Err(e) => {
+                log::warn!("Web Audio is not available, audio is disabled: {:?}", e);
+                return None;
+            }
+        };
+        let on_gesture = resume_on_gesture(&context)
+            .map_err(|e| log::error!("Failed to listen for the first user gesture: {:?}", e))
+            .ok();
+
+        Some(WebAudio {
+            context,
+            buffers: Rc::default(),
+            decode_callbacks: RefCell::default(),
+            _on_gesture: on_gesture,
+        })
+    }
+
+    /// Returns the decoded sound for a source, starting to decode it once it has loaded.
+    pub fn decoded(&self, handle: &SourceHandle, storage: &AssetStorage<Source>) -> Decoded {
+        if let Some(decoded) = self.buffers.borrow().get(&handle.id()) {
+            if !matches!(decoded, Decoded::Decoding) {
+                self.decode_callbacks.borrow_mut().remove(&handle.id());
+            }
+            return decoded.clone();
+        }
+        let source = match storage.get(handle) {
+            Some(source) => source,
+            None => return Decoded::Decoding,
+        };
+
+        self.buffers
+            .borrow_mut()
+            .insert(handle.id(), Decoded::Decoding);
+        if let Err(e) = self.decode(handle.id(), source) {
+            log::error!("Failed to decode sound: {:?}", e);
+            self.buffers
+                .borrow_mut()
+                .insert(handle.id(), Decoded::Failed);
+        }
+        Decoded::Decoding
+    }
+
+    fn decode(&self, id: u32, source: &Source) -> Result<(), JsValue> {
+        // Copy the bytes out of the shared wasm memory, `decodeAudioData` rejects shared buffers.
+        let bytes = Uint8Array::from(source.as_ref());
+        let promise = self.context.decode_audio_data(&bytes.buffer())?;
+
+        let on_decoded = {
+            let buffers = self.buffers.clone();
+            Closure::wrap(Box::new(move |buffer: JsValue| {
+                let decoded = match buffer.dyn_into::<AudioBuffer>() {
+                    Ok(buffer) => Decoded::Ready(buffer),
+                    Err(_) => Decoded::Failed,
+                };
+                buffers.borrow_mut().insert(id, decoded);
+            }) as Box<dyn FnMut(JsValue)>)
+        };
+        let on_error = {
+            let buffers = self.buffers.clone();
+            Closure::wrap(Box::new(move |e: JsValue| {
+                log::error!("Failed to decode sound: {:?}", e);
+                buffers.borrow_mut().insert(id, Decoded::Failed);
+            }) as Box<dyn FnMut(JsValue)>)
+        };
+        let _ = promise.then2(&on_decoded, &on_error);
+
+        // A closure cannot be dropped while it runs, so the callbacks are kept until the next
+        // lookup of the sound sees the result.
+        self.decode_callbacks
+            .borrow_mut()
+            .insert(id, (on_decoded, on_error));
+
+        Ok(())
+    }
+
+    /// Plays a sound once. Impacts are panned and pitched, other sounds play centred.
+    pub fn play(&self, buffer: &AudioBuffer, volume: f32, impact: Option<Impact>) {
+        if let Err(e) = self.try_play(buffer, volume, impact) {
+            log::error!("Failed to play sound: {:?}", e);
+        }
+    }
+
+    fn try_play(
+        &self,
+        buffer: &AudioBuffer,
+        volume: f32,
+        impact: Option<Impact>,
+    ) -> Result<(), JsValue> {
+        let source = self.context.create_buffer_source()?;
+        source.set_buffer(Some(buffer));
+        let gain = self.context.create_gain()?;
+        source.connect_with_audio_node(&gain)?;
+
+        let output: AudioNode = match impact {
+            Some(impact) => {
+                source.playback_rate().set_value(impact.pitch);
+                gain.gain().set_value(volume * impact.volume);
+                let panner = self.context.create_stereo_panner()?;
+                panner.pan().set_value(impact.pan);
+                gain.connect_with_audio_node(&panner)?;
+                panner.into()
+            }
+            None => {
+                gain.gain().set_value(volume);
+                gain.into()
+            }
+        };
+        output.connect_with_audio_node(&self.context.destination())?;
+
+        source.start()
+    }
+
+    /// Starts playing a music track, silent until it fades in.
+    pub fn start_track(&self, buffer: &AudioBuffer) -> TrackStart<WebTrack> {
+        match self.try_start_track(buffer) {
+            Ok(track) => TrackStart::Playing(track),
+            Err(e) => {
+                log::error!("Failed to play music track: {:?}", e);
+                TrackStart::Failed
+            }
+        }
+    }
+
+    fn try_start_track(&self, buffer: &AudioBuffer) -> Result<WebTrack, JsValue> {
+        let source = self.context.create_buffer_source()?;
+        source.set_buffer(Some(buffer));
+        let gain = self.context.create_gain()?;
+        gain.gain().set_value(0.0);
+        source.connect_with_audio_node(&gain)?;
+        gain.connect_with_audio_node(&self.context.destination())?;
+        source.start()?;
+
+        Ok(WebTrack {
+            context: self.context.clone(),
+            source,
+            gain,
+            ends_at: self.context.current_time() + buffer.duration(),
+        })
+    }
+}
+
+/// A music track playing through Web Audio. It stops when dropped.
+pub struct WebTrack {
+    context: AudioContext,
+    source: AudioBufferSourceNode,
+    gain: GainNode,
+    /// Context time at which the track is over.
+    ends_at: f64,
+}
+
+impl MusicTrack for WebTrack {
+    fn set_volume(&mut self, volume: f32) {
+        self.gain.gain().set_value(volume);
+    }
+
+    fn is_finished(&self) -> bool {
+        self.context.current_time() >= self.ends_at
+    }
+}
+
+impl Drop for WebTrack {
+    fn drop(&mut self) {
+        let _ = self.source.stop();
+        let _ = self.gain.disconnect();
+    }
+}
+
+/// Browsers keep audio suspended until the user interacts with the page.
+///
+/// Returns the listener, which must be kept for as long as it listens.
+fn resume_on_gesture(context: &AudioContext) -> Result<Closure<dyn FnMut()>, JsValue> {
+    let window = web_sys::window().ok_or_else(|| JsValue::from_str("no window"))?;
+
+    let on_gesture = {
+        let context = context.clone();
+        Closure::wrap(Box::new(move || {
+            // Resuming a running context does nothing.
+            if let Err(e) = context.resume() {
+                log::error!("Failed to resume audio: {:?}", e);
+            }
+        }) as Box<dyn FnMut()>)
+    };
+
+    for event_name in &["pointerdown", "keydown", "touchstart"] {
+        window.add_event_listener_with_callback(event_name, on_gesture.as_ref().unchecked_ref())?;
+    }
+
+    Ok(on_gesture)
+}
@@ -0,0 +1,97 @@
+//! Loading and saving of the player's input bindings.
+
+#[cfg(not(feature = "wasm"))]
+use crate::systems::ConfigFiles;
+use amethyst::{
+    ecs::{World, WorldExt},
+    input::{Axis, Bindings, Button, StringBindings},
+    Error,
+};
+
+/// Location of the bindings, relative to the application root.
+pub const BINDINGS_PATH: &str = "config/input.ron";
+
+/// Returns the storage key the bindings are saved to: the user override file layered over the
+/// bindings file, such as `config/input.user.ron` for `config/input.ron`, so that the bindings
+/// file keeps its comments.
+#[cfg(not(feature = "wasm"))]
+fn saved_bindings_key(world: &World) -> Result<String, Error> {
+    let user_file = world
+        .read_resource::<ConfigFiles>()
+        .input
+        .files()
+        .pop()
+        .ok_or_else(|| Error::from_string("the bindings are not read from a file"))?;
+    // Keys are relative to the application root, which files given on the command line may be
+    // outside of.
+    let user_file = std::env::current_dir()?.join(user_file);
+    let root = amethyst::utils::application_root_dir()?;
+    Ok(match user_file.strip_prefix(&root) {
+        Ok(relative) => relative
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/"),
+        Err(_) => user_file.to_string_lossy().into_owned(),
+    })
+}
+
+/// Returns the storage key the bindings are saved to in `localStorage`.
+#[cfg(feature = "wasm")]
+fn saved_bindings_key(_world: &World) -> Result<String, Error> {
+    Ok(BINDINGS_PATH.to_string())
+}
+
+/// Saves the bindings where they are read from the next time the game starts.
+pub fn save(world: &World, bindings: &Bindings<StringBindings>) -> Result<(), Error> {
+    crate::storage::save(&saved_bindings_key(world)?, bindings)
+}
+
+/// Returns the bindings previously saved in the browser's `localStorage`, if any.
+#[cfg(feature = "wasm")]
+pub fn load_saved() -> Option<Bindings<StringBindings>> {
+    crate::storage::load(BINDINGS_PATH)
+        .map_err(|e| log::error!("Ignoring saved bindings: {}", e))
+        .ok()
+        .flatten()
+}
+
+/// Returns the keyboard style bindings of an axis, searching within `Multiple` axes.
+pub fn emulated_buttons(axis: &Axis) -> Option<(Button, Button)> {
+    match axis {
+        Axis::Emulated { pos, neg } => Some((*pos, *neg)),
+        Axis::Multiple(axes) => axes.iter().find_map(emulated_buttons),
+        _ => None,
+    }
+}
+
+/// Returns a copy of the axis with its first keyboard style binding replaced.
+pub fn with_emulated_buttons(axis: &Axis, pos: Button, neg: Button) -> Axis {
+    match axis {
+        Axis::Emulated { .. } => Axis::Emulated { pos, neg },
+        Axis::Multiple(axes) => {
+            let mut axes = axes.clone();
+            match axes
+                .iter()
+                .position(|axis| emulated_buttons(axis).is_some())
+            {
+                Some(index) => axes[index] = with_emulated_buttons(&axes[index], pos, neg),
+                None => axes.push(Axis::Emulated { pos, neg }),
+            }
+            Axis::Multiple(axes)
+        }
+        axis => Axis::Multiple(vec![Axis::Emulated { pos, neg }, axis.clone()]),
+    }
+}
+
+/// Returns a short human readable name for a button.
+pub fn button_name(button: &Button) -> String {
+    match button {
+        Button::Key(key) => format!("{:?}", key),
+        Button::Mouse(mouse_button) => format!("Mouse {:?}", mouse_button),
+        Button::Controller(controller_id, controller_button) => {
+            format!("Controller {} {:?}", controller_id, controller_button)
+        }
+        button => format!("{:?}", button),
+    }
+}
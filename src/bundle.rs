@@ -1,4 +1,15 @@
-use crate::systems::{AudioSystem, BounceSystem, MoveBallsSystem, PaddleSystem, WinnerSystem};
+use crate::systems::{
+    AudioSettingsSystem, BounceSystem, ControllerAssignmentSystem, EventLogSystem, FrameTimeSystem,
+    MatchStatsSystem, MoveBallsSystem, PaddleAiSystem, PaddleInputSystem, PaddlePointerSystem,
+    PaddleSystem, RecordSystem, ScoreUiSystem, ToastSystem, TouchSystem, ViewportSystem,
+    WinnerSystem,
+};
+#[cfg(not(feature = "wasm"))]
+use crate::systems::{AudioSystem, ConfigReloadSystem, MusicDirectorSystem};
+#[cfg(feature = "wasm")]
+use crate::systems::{
+    CanvasResizeSystem, HostEventSystem, HostSnapshotSystem, WebAudioSystem, WebGamepadSystem,
+};
 use amethyst::{
     core::bundle::SystemBundle,
     ecs::prelude::{DispatcherBuilder, World},
@@ -7,7 +18,35 @@ use amethyst::{
 
 /// A bundle is a convenient way to initialise related resources, components and systems in a
 /// world. This bundle prepares the world for a game of pong.
-pub struct PongBundle;
+pub struct PongBundle {
+    /// Whether the systems playing music and sound effects are added.
+    audio: bool,
+    /// Whether the systems drawing the game and reading the pointer are added.
+    display: bool,
+}
+
+impl Default for PongBundle {
+    fn default() -> Self {
+        PongBundle {
+            audio: true,
+            display: true,
+        }
+    }
+}
+
+impl PongBundle {
+    /// Sets whether music and sound effects are played.
+    pub fn with_audio(mut self, audio: bool) -> Self {
+        self.audio = audio;
+        self
+    }
+
+    /// Sets whether the game is shown in a window, which headless runs leave out.
+    pub fn with_display(mut self, display: bool) -> Self {
+        self.display = display;
+        self
+    }
+}
 
 impl<'a, 'b> SystemBundle<'a, 'b> for PongBundle {
     fn build(
@@ -15,23 +54,118 @@ impl<'a, 'b> SystemBundle<'a, 'b> for PongBundle {
         _world: &mut World,
         builder: &mut DispatcherBuilder<'a, 'b>,
     ) -> Result<(), Error> {
-        builder.add(PaddleSystem, "paddle_system", &["input_system"]);
-        builder.add(MoveBallsSystem, "ball_system", &[]);
+        if self.display {
+            builder.add(ViewportSystem::default(), "viewport_system", &[]);
+            builder.add(TouchSystem::default(), "touch_system", &[]);
+        }
+        // Every system moving the game on waits for the length of the frame.
+        builder.add(FrameTimeSystem, "frame_time_system", &[]);
+        builder.add(
+            ControllerAssignmentSystem::default(),
+            "controller_assignment_system",
+            &["input_system"],
+        );
+        builder.add(
+            PaddleInputSystem,
+            "paddle_input_system",
+            &[
+                "input_system",
+                "controller_assignment_system",
+                "frame_time_system",
+            ],
+        );
         builder.add(
-            BounceSystem,
-            "collision_system",
-            &["paddle_system", "ball_system"],
+            PaddleSystem,
+            "paddle_system",
+            &["input_system", "paddle_input_system"],
         );
         builder.add(
-            WinnerSystem,
-            "winner_system",
-            &["paddle_system", "ball_system"],
+            PaddleAiSystem::default(),
+            "paddle_ai_system",
+            &["paddle_system"],
+        );
+        // Systems moving the paddles, which the ball and recording wait for.
+        let mut paddle_systems = vec!["paddle_system", "paddle_ai_system"];
+        if self.display {
+            builder.add(
+                PaddlePointerSystem,
+                "paddle_pointer_system",
+                &["input_system", "touch_system", "paddle_system"],
+            );
+            paddle_systems.push("paddle_pointer_system");
+        }
+        builder.add(MoveBallsSystem, "ball_system", &["frame_time_system"]);
+        let mut ball_dependencies = paddle_systems.clone();
+        ball_dependencies.push("ball_system");
+        builder.add(BounceSystem, "collision_system", &ball_dependencies);
+        builder.add(WinnerSystem, "winner_system", &ball_dependencies);
+        builder.add(RecordSystem, "record_system", &paddle_systems);
+        if self.display {
+            builder.add(
+                ScoreUiSystem::default(),
+                "score_ui_system",
+                &["winner_system"],
+            );
+        }
+        builder.add(
+            MatchStatsSystem::default(),
+            "match_stats_system",
+            &["winner_system", "collision_system"],
         );
         builder.add(
-            AudioSystem::default(),
-            "pong_audio_system",
+            EventLogSystem::default(),
+            "event_log_system",
             &["winner_system", "collision_system"],
         );
+        builder.add(
+            AudioSettingsSystem::default(),
+            "audio_settings_system",
+            &["input_system"],
+        );
+        builder.add(
+            ToastSystem::default(),
+            "toast_system",
+            &["controller_assignment_system", "audio_settings_system"],
+        );
+        if self.audio {
+            #[cfg(not(feature = "wasm"))]
+            builder.add(
+                AudioSystem::default(),
+                "pong_audio_system",
+                &["winner_system", "collision_system"],
+            );
+            #[cfg(not(feature = "wasm"))]
+            builder.add(
+                MusicDirectorSystem::default(),
+                "music_director_system",
+                &["winner_system", "audio_settings_system"],
+            );
+            // Web Audio is only available on the main thread, where thread local systems run.
+            #[cfg(feature = "wasm")]
+            builder.add_thread_local(WebAudioSystem::default());
+        }
+
+        // Changes to the config files are applied while the game runs. The window title is set
+        // from the main thread.
+        #[cfg(not(feature = "wasm"))]
+        builder.add_thread_local(ConfigReloadSystem::default());
+
+        // The embedding page reads the state of the game and listens to its events.
+        #[cfg(feature = "wasm")]
+        {
+            builder.add(
+                HostSnapshotSystem,
+                "host_snapshot_system",
+                &["winner_system", "collision_system", "match_stats_system"],
+            );
+            builder.add_thread_local(HostEventSystem::default());
+            // The canvas follows the size of its container, and the window is only available
+            // on the main thread.
+            builder.add_thread_local(CanvasResizeSystem);
+            // Gamepads are polled through the Gamepad API, which is only available on the main
+            // thread.
+            builder.add_thread_local(WebGamepadSystem::default());
+        }
         Ok(())
     }
 }
@@ -0,0 +1,305 @@
+//! Command line options of the native game.
+
+use std::{fmt, path::PathBuf, str::FromStr};
+
+use crate::game_config::GameMode;
+
+/// Help text, printed by `--help` and after usage errors.
+pub const USAGE: &str = "\
+Usage: pong [OPTIONS]
+
+Options:
+    --config <FILE>       Game config, instead of config/game.ron
+    --bindings <FILE>     Input bindings, instead of config/input.ron
+    --width <PIXELS>      Window width
+    --height <PIXELS>     Window height
+    --fullscreen          Open fullscreen on the primary monitor
+    --seed <NUMBER>       Seed of the serves, so that they can be replayed
+    --mode <MODE>         Either `match` or `endless`
+    --ai <SIDE>           Paddles driven by the computer: `left`, `right` or `both`
+    --record <FILE>       Record the match to FILE when the game quits
+    --replay <FILE>       Replay a recorded match, then quit
+    --stats <FILE>        Write the stats of the match to FILE as JSON when it ends
+    --headless            Run without a window or audio, and quit once the match is over.
+                          Needs `--ai both` or `--replay`, and cannot be used with
+                          `--mode endless`
+    --max-frames <N>      Quit a headless run after N frames, even if the match is not over.
+                          216000 by default, an hour at 60 frames a second
+    --print-config        Print every config value and where it comes from, then quit
+    --analyze <FILE>      Summarise an event log written by the game, then quit. The goals are
+                          placed in the arena of the game config
+    -h, --help            Print this help
+
+Options are applied on top of the config files. Each config file, such as config/game.ron,
+can be overridden by a user file next to it, such as config/game.user.ron, and by environment
+variables holding RON values, such as PONG_GAME__BALL__RADIUS=3.0.";
+
+/// Sides that can be handed to the computer with `--ai`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AiSides {
+    Left,
+    Right,
+    Both,
+}
+
+/// Options given on the command line.
+#[derive(Debug, Default)]
+pub struct Cli {
+    pub config: Option<PathBuf>,
+    pub bindings: Option<PathBuf>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub fullscreen: bool,
+    pub seed: Option<u64>,
+    pub mode: Option<GameMode>,
+    pub ai: Option<AiSides>,
+    pub record: Option<PathBuf>,
+    pub replay: Option<PathBuf>,
+    pub stats: Option<PathBuf>,
+    pub headless: bool,
+    /// Frames a headless run quits after.
+    pub max_frames: Option<u64>,
+    /// Whether the config values are printed instead of running the game.
+    pub print_config: bool,
+    /// Event log summarised instead of running the game.
+    pub analyze: Option<PathBuf>,
+    /// Whether the help text was asked for.
+    pub help: bool,
+}
+
+/// Invalid command line, with a message for the user.
+#[derive(Debug)]
+pub struct CliError(String);
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for CliError {}
+
+impl Cli {
+    /// Parses the options of the running process.
+    pub fn from_env() -> Result<Self, CliError> {
+        Self::parse(std::env::args().skip(1))
+    }
+
+    /// Parses `args`, which exclude the program name.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, CliError> {
+        let mut cli = Cli::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            // Values may be given as `--option value` or `--option=value`.
+            let (option, inline_value) = match arg.find('=') {
+                Some(index) if arg.starts_with("--") => {
+                    (arg[..index].to_string(), Some(arg[index + 1..].to_string()))
+                }
+                _ => (arg, None),
+            };
+            let mut value = || {
+                inline_value
+                    .clone()
+                    .or_else(|| args.next())
+                    .ok_or_else(|| CliError(format!("`{}` needs a value", option)))
+            };
+
+            match option.as_str() {
+                "--config" => cli.config = Some(PathBuf::from(value()?)),
+                "--bindings" => cli.bindings = Some(PathBuf::from(value()?)),
+                "--width" => cli.width = Some(parse_value(&option, &value()?)?),
+                "--height" => cli.height = Some(parse_value(&option, &value()?)?),
+                "--seed" => cli.seed = Some(parse_value(&option, &value()?)?),
+                "--mode" => {
+                    cli.mode = Some(match value()?.as_str() {
+                        "match" => GameMode::Match,
+                        "endless" => GameMode::Endless,
+                        other => {
+                            return Err(CliError(format!(
+                                "invalid mode `{}`, expected `match` or `endless`",
+                                other
+                            )))
+                        }
+                    })
+                }
+                "--ai" => {
+                    cli.ai = Some(match value()?.as_str() {
+                        "left" => AiSides::Left,
+                        "right" => AiSides::Right,
+                        "both" => AiSides::Both,
+                        other => {
+                            return Err(CliError(format!(
+                                "invalid side `{}`, expected `left`, `right` or `both`",
+                                other
+                            )))
+                        }
+                    })
+                }
+                "--record" => cli.record = Some(PathBuf::from(value()?)),
+                "--replay" => cli.replay = Some(PathBuf::from(value()?)),
+                "--stats" => cli.stats = Some(PathBuf::from(value()?)),
+                "--max-frames" => cli.max_frames = Some(parse_value(&option, &value()?)?),
+                "--analyze" => cli.analyze = Some(PathBuf::from(value()?)),
+                "--fullscreen" | "--headless" | "--print-config" | "--help" | "-h"
+                    if inline_value.is_some() =>
+                {
+                    return Err(CliError(format!("`{}` takes no value", option)))
+                }
+                "--fullscreen" => cli.fullscreen = true,
+                "--headless" => cli.headless = true,
+                "--print-config" => cli.print_config = true,
+                "--help" | "-h" => cli.help = true,
+                _ => return Err(CliError(format!("unknown option `{}`", option))),
+            }
+        }
+
+        if cli.help {
+            return Ok(cli);
+        }
+        cli.validate()?;
+        Ok(cli)
+    }
+
+    /// Rejects options that cannot be used together.
+    fn validate(&self) -> Result<(), CliError> {
+        if self.replay.is_some() {
+            if self.ai.is_some() {
+                return Err(CliError(
+                    "`--ai` cannot be used with `--replay`, which moves both paddles".to_string(),
+                ));
+            }
+            if self.record.is_some() {
+                return Err(CliError(
+                    "`--record` cannot be used with `--replay`".to_string(),
+                ));
+            }
+            if self.seed.is_some() || self.mode.is_some() || self.config.is_some() {
+                return Err(CliError(
+                    "`--replay` plays with the config it was recorded with, so `--config`, \
+                     `--seed` and `--mode` cannot be used with it"
+                        .to_string(),
+                ));
+            }
+        }
+        if self.headless && self.replay.is_none() && self.ai != Some(AiSides::Both) {
+            return Err(CliError(
+                "`--headless` has no one to play, use it with `--ai both` or `--replay`"
+                    .to_string(),
+            ));
+        }
+        if self.headless && self.mode == Some(GameMode::Endless) {
+            return Err(CliError(
+                "`--headless` quits once the match is over, so it cannot be used with \
+                 `--mode endless`"
+                    .to_string(),
+            ));
+        }
+        if self.max_frames.is_some() && !self.headless {
+            return Err(CliError(
+                "`--max-frames` can only be used with `--headless`".to_string(),
+            ));
+        }
+        if self.headless && self.fullscreen {
+            return Err(CliError(
+                "`--fullscreen` cannot be used with `--headless`".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn parse_value<T: FromStr>(option: &str, value: &str) -> Result<T, CliError>
+where
+    T::Err: fmt::Display,
+{
+    value
+        .parse()
+        .map_err(|e| CliError(format!("invalid value `{}` for `{}`: {}", value, option, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Cli, CliError> {
+        Cli::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    /// Returns the message of the error `args` are rejected with.
+    fn error(args: &[&str]) -> String {
+        match parse(args) {
+            Ok(cli) => panic!("expected `{:?}` to be rejected, got {:?}", args, cli),
+            Err(e) => e.to_string(),
+        }
+    }
+
+    #[test]
+    fn values_follow_options_or_are_inline() {
+        let cli = parse(&[
+            "--seed=42",
+            "--width",
+            "800",
+            "--config=my=game.ron",
+            "--ai=left",
+            "--mode",
+            "endless",
+        ])
+        .unwrap();
+        assert_eq!(cli.seed, Some(42));
+        assert_eq!(cli.width, Some(800));
+        assert_eq!(cli.config, Some(PathBuf::from("my=game.ron")));
+        assert_eq!(cli.ai, Some(AiSides::Left));
+        assert!(matches!(cli.mode, Some(GameMode::Endless)));
+    }
+
+    #[test]
+    fn rejects_missing_and_invalid_values() {
+        assert_eq!(error(&["--record"]), "`--record` needs a value");
+        assert_eq!(
+            error(&["--ai=middle"]),
+            "invalid side `middle`, expected `left`, `right` or `both`"
+        );
+        assert!(error(&["--width", "wide"]).starts_with("invalid value `wide` for `--width`"));
+        assert_eq!(error(&["--headless=yes"]), "`--headless` takes no value");
+    }
+
+    #[test]
+    fn rejects_unknown_options() {
+        assert_eq!(error(&["--speed", "2"]), "unknown option `--speed`");
+        assert_eq!(error(&["--speed=2"]), "unknown option `--speed`");
+        assert_eq!(error(&["game.ron"]), "unknown option `game.ron`");
+    }
+
+    #[test]
+    fn headless_needs_someone_to_play() {
+        let message = "`--headless` has no one to play, use it with `--ai both` or `--replay`";
+        assert_eq!(error(&["--headless"]), message);
+        assert_eq!(error(&["--headless", "--ai", "left"]), message);
+        assert!(parse(&["--headless", "--ai", "both"]).unwrap().headless);
+        assert!(
+            parse(&["--headless", "--replay", "match.ron"])
+                .unwrap()
+                .headless
+        );
+    }
+
+    #[test]
+    fn headless_runs_end() {
+        assert_eq!(
+            error(&["--headless", "--ai", "both", "--mode", "endless"]),
+            "`--headless` quits once the match is over, so it cannot be used with \
+             `--mode endless`"
+        );
+        assert_eq!(
+            error(&["--ai", "both", "--max-frames", "600"]),
+            "`--max-frames` can only be used with `--headless`"
+        );
+        let cli = parse(&["--headless", "--ai", "both", "--max-frames=600"]).unwrap();
+        assert_eq!(cli.max_frames, Some(600));
+    }
+
+    #[test]
+    fn help_skips_validation() {
+        assert!(parse(&["--headless", "--help"]).unwrap().help);
+    }
+}
@@ -0,0 +1,38 @@
+//! Assets and config defaults compiled into the binary, so the game runs without the assets
+//! and config directories.
+
+use amethyst::{assets::Source, Error};
+
+/// Every file of the assets directory, keyed by its path relative to that directory. The list
+/// is generated by `build.rs`.
+static ASSETS: &[(&str, &[u8])] = include!(concat!(env!("OUT_DIR"), "/embedded_assets.rs"));
+
+/// Every file of the config directory, keyed by its file name, used when the file is missing.
+static CONFIG: &[(&str, &str)] = include!(concat!(env!("OUT_DIR"), "/embedded_config.rs"));
+
+/// Returns the embedded copy of a config file, such as `game.ron`.
+pub fn config(file_name: &str) -> Option<&'static str> {
+    CONFIG
+        .iter()
+        .find(|(name, _)| *name == file_name)
+        .map(|(_, text)| *text)
+}
+
+/// Asset source serving the embedded assets from memory.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct EmbeddedAssets;
+
+impl Source for EmbeddedAssets {
+    fn modified(&self, _path: &str) -> Result<u64, Error> {
+        // Embedded assets never change while the game runs.
+        Ok(0)
+    }
+
+    fn load(&self, path: &str) -> Result<Vec<u8>, Error> {
+        ASSETS
+            .iter()
+            .find(|(asset_path, _)| *asset_path == path)
+            .map(|(_, bytes)| bytes.to_vec())
+            .ok_or_else(|| Error::from_string(format!("`{}` is not embedded", path)))
+    }
+}
@@ -0,0 +1,80 @@
+//! Errors that keep the game from starting, or from going on.
+
+use std::fmt;
+
+use crate::layered_config::ConfigSource;
+
+/// Line and column in a RON text, both starting at 1.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+#[derive(Debug)]
+pub enum PongError {
+    /// A config could not be read, or is invalid.
+    Config {
+        /// Name of the config, such as `game`.
+        name: &'static str,
+        /// Layer the error is in, `Default` when the defaults themselves are invalid.
+        origin: ConfigSource,
+        /// Where the error is in the layer, when known.
+        position: Option<Position>,
+        message: String,
+    },
+    /// An asset could not be read or loaded.
+    Asset { path: String, message: String },
+    /// A resource the game relies on is missing from the world.
+    MissingResource(&'static str),
+}
+
+impl PongError {
+    /// Returns the error of a RON text that could not be deserialized.
+    pub fn ron(name: &'static str, origin: ConfigSource, e: ron::de::Error) -> Self {
+        let (position, message) = match e {
+            ron::de::Error::Parser(code, position) => (
+                Some(Position {
+                    line: position.line,
+                    column: position.col,
+                }),
+                format!("{:?}", code),
+            ),
+            e => (None, e.to_string()),
+        };
+        PongError::Config {
+            name,
+            origin,
+            position,
+            message,
+        }
+    }
+}
+
+impl fmt::Display for PongError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PongError::Config {
+                name,
+                origin,
+                position,
+                message,
+            } => {
+                write!(f, "Invalid {} config", name)?;
+                if *origin != ConfigSource::Default {
+                    write!(f, " in {}", origin)?;
+                }
+                if let Some(position) = position {
+                    write!(f, " at line {}, column {}", position.line, position.column)?;
+                }
+                write!(f, ": {}", message)
+            }
+            PongError::Asset { path, message } => {
+                write!(f, "Failed to load `{}`: {}", path, message)
+            }
+            PongError::MissingResource(name) => write!(f, "Missing resource `{}`", name),
+        }
+    }
+}
+
+impl std::error::Error for PongError {}
@@ -1,5 +1,122 @@
-#[derive(Clone, Copy, Debug, PartialEq)]
+use amethyst::ecs::Entity;
+use serde::{Deserialize, Serialize};
+
+use crate::Side;
+
+/// Surface the ball bounced off.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Surface {
+    TopWall,
+    BottomWall,
+    LeftPaddle,
+    RightPaddle,
+}
+
+impl Surface {
+    /// Returns whether the surface is one of the paddles.
+    pub fn is_paddle(self) -> bool {
+        match self {
+            Surface::LeftPaddle | Surface::RightPaddle => true,
+            Surface::TopWall | Surface::BottomWall => false,
+        }
+    }
+}
+
+/// Something that happened in play, for the audio, the UI, the event log and the page.
+///
+/// The entity of the ball is left out when events are serialized.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(tag = "type")]
 pub enum PongEvent {
+    /// The ball bounced off a wall or a paddle.
+    Bounce {
+        #[serde(skip)]
+        ball: Option<Entity>,
+        /// Surface the ball bounced off.
+        surface: Surface,
+        /// Position of the ball in the arena when it bounced.
+        position: [f32; 2],
+        /// Velocity of the ball after the bounce.
+        velocity: [f32; 2],
+        /// Speed of the ball towards the surface it bounced off.
+        speed: f32,
+        frame: u64,
+    },
+    /// The ball went past a paddle.
+    Score {
+        #[serde(skip)]
+        ball: Option<Entity>,
+        /// Side that scored.
+        side: Side,
+        /// Position of the ball when it went in, before it was put back in the centre.
+        position: [f32; 2],
+        /// Velocity of the ball when it went in.
+        velocity: [f32; 2],
+        /// Scores after the goal.
+        score_left: i32,
+        score_right: i32,
+        frame: u64,
+    },
+    /// The ball was put into play.
+    Serve {
+        #[serde(skip)]
+        ball: Option<Entity>,
+        position: [f32; 2],
+        velocity: [f32; 2],
+        frame: u64,
+    },
+    /// A player reached the score limit.
+    GameOver {
+        winner: Side,
+        score_left: i32,
+        score_right: i32,
+        frame: u64,
+    },
+}
+
+impl PongEvent {
+    /// Returns the kind of the event, without its data.
+    pub fn kind(&self) -> PongEventKind {
+        match self {
+            PongEvent::Bounce { .. } => PongEventKind::Bounce,
+            PongEvent::Score { .. } => PongEventKind::Score,
+            PongEvent::Serve { .. } => PongEventKind::Serve,
+            PongEvent::GameOver { .. } => PongEventKind::GameOver,
+        }
+    }
+
+    /// Returns the number of the frame the event happened in.
+    pub fn frame(&self) -> u64 {
+        match *self {
+            PongEvent::Bounce { frame, .. }
+            | PongEvent::Score { frame, .. }
+            | PongEvent::Serve { frame, .. }
+            | PongEvent::GameOver { frame, .. } => frame,
+        }
+    }
+
+    /// Returns where the ball was and how it moved, for events about the ball.
+    pub fn ball_motion(&self) -> Option<([f32; 2], [f32; 2])> {
+        match *self {
+            PongEvent::Bounce {
+                position, velocity, ..
+            }
+            | PongEvent::Score {
+                position, velocity, ..
+            }
+            | PongEvent::Serve {
+                position, velocity, ..
+            } => Some((position, velocity)),
+            PongEvent::GameOver { .. } => None,
+        }
+    }
+}
+
+/// Kind of a `PongEvent`, used to refer to events in configuration.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum PongEventKind {
     Bounce,
     Score,
+    Serve,
+    GameOver,
 }
@@ -0,0 +1,259 @@
+//! Structured log of the gameplay events, written as JSON Lines or kept in memory.
+
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+};
+#[cfg(not(feature = "wasm"))]
+use std::{
+    fs::{File, OpenOptions},
+    io::{LineWriter, Write},
+    path::Path,
+};
+
+use crate::{
+    event::PongEvent,
+    game_config::{EventLogConfig, EventLogOutput},
+    Side,
+};
+
+/// Events kept in memory when the config asks for a file that cannot be written.
+#[cfg(feature = "wasm")]
+const FALLBACK_CAPACITY: usize = 1000;
+
+/// Position and velocity of the ball.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct BallSnapshot {
+    pub position: [f32; 2],
+    pub velocity: [f32; 2],
+}
+
+/// An event, and the state of the game when it happened.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct EventRecord {
+    /// Number of the frame the event happened in.
+    pub frame: u64,
+    /// Game time of the event, in seconds, which stops while the game is paused.
+    pub time: f64,
+    pub event: PongEvent,
+    /// The ball, `None` before the first serve.
+    pub ball: Option<BallSnapshot>,
+    /// Heights of the paddles.
+    pub left_paddle: Option<f32>,
+    pub right_paddle: Option<f32>,
+    /// Side that scored, for `Score` events.
+    pub scorer: Option<Side>,
+}
+
+/// Destination of the event records, shared with the embedding page on wasm.
+#[derive(Clone, Default)]
+pub struct EventLog {
+    sink: Arc<Mutex<Sink>>,
+}
+
+enum Sink {
+    Off,
+    Memory {
+        records: VecDeque<EventRecord>,
+        capacity: usize,
+    },
+    #[cfg(not(feature = "wasm"))]
+    File(LineWriter<File>),
+}
+
+impl Default for Sink {
+    fn default() -> Self {
+        Sink::Off
+    }
+}
+
+impl EventLog {
+    /// Returns the log the config asks for, opening its file if any.
+    #[cfg(not(feature = "wasm"))]
+    pub fn new(config: &EventLogConfig) -> std::io::Result<Self> {
+        let sink = match &config.output {
+            EventLogOutput::Off => Sink::Off,
+            EventLogOutput::Memory { capacity } => Sink::memory(*capacity),
+            EventLogOutput::File { path } => Sink::File(LineWriter::new(
+                OpenOptions::new().create(true).append(true).open(path)?,
+            )),
+        };
+        Ok(EventLog::with_sink(sink))
+    }
+
+    /// Returns the log the config asks for. There are no files on the web, so the latest events
+    /// are kept in memory instead.
+    #[cfg(feature = "wasm")]
+    pub fn new(config: &EventLogConfig) -> Self {
+        let sink = match &config.output {
+            EventLogOutput::Off => Sink::Off,
+            EventLogOutput::Memory { capacity } => Sink::memory(*capacity),
+            EventLogOutput::File { path } => {
+                log::warn!(
+                    "Cannot write the event log to `{}` on the web, keeping the last {} events in \
+                     memory instead.",
+                    path,
+                    FALLBACK_CAPACITY
+                );
+                Sink::memory(FALLBACK_CAPACITY)
+            }
+        };
+        EventLog::with_sink(sink)
+    }
+
+    fn with_sink(sink: Sink) -> Self {
+        EventLog {
+            sink: Arc::new(Mutex::new(sink)),
+        }
+    }
+
+    /// Returns whether events are logged at all.
+    pub fn is_enabled(&self) -> bool {
+        self.sink
+            .lock()
+            .map(|sink| match *sink {
+                Sink::Off => false,
+                _ => true,
+            })
+            .unwrap_or(false)
+    }
+
+    /// Logs the record, dropping the oldest one when the memory is full. Logging stops after the
+    /// file fails to be written.
+    pub fn push(&self, record: EventRecord) {
+        let mut sink = match self.sink.lock() {
+            Ok(sink) => sink,
+            Err(_) => return,
+        };
+        match &mut *sink {
+            Sink::Off => {}
+            Sink::Memory { records, capacity } => {
+                if records.len() == *capacity {
+                    records.pop_front();
+                }
+                if *capacity > 0 {
+                    records.push_back(record);
+                }
+            }
+            #[cfg(not(feature = "wasm"))]
+            Sink::File(writer) => {
+                let written = serde_json::to_string(&record)
+                    .map_err(std::io::Error::from)
+                    .and_then(|line| writeln!(writer, "{}", line));
+                if let Err(e) = written {
+                    log::error!(
+                        "Failed to write the event log, no more events are logged: {}",
+                        e
+                    );
+                    *sink = Sink::Off;
+                }
+            }
+        }
+    }
+
+    /// Returns the records kept in memory, oldest first.
+    #[cfg(feature = "wasm")]
+    pub fn records(&self) -> Vec<EventRecord> {
+        match self.sink.lock() {
+            Ok(sink) => match &*sink {
+                Sink::Memory { records, .. } => records.iter().cloned().collect(),
+                _ => Vec::new(),
+            },
+            Err(_) => Vec::new(),
+        }
+    }
+}
+
+impl Sink {
+    fn memory(capacity: usize) -> Self {
+        Sink::Memory {
+            records: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+}
+
+/// Reads the records of a JSON Lines event log.
+#[cfg(not(feature = "wasm"))]
+pub fn load(path: &Path) -> Result<Vec<EventRecord>, amethyst::Error> {
+    let text = std::fs::read_to_string(path)?;
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            serde_json::from_str(line).map_err(|e| {
+                amethyst::Error::from_string(format!(
+                    "Invalid event at line {} of `{}`: {}",
+                    index + 1,
+                    path.display(),
+                    e
+                ))
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(event: PongEvent) -> EventRecord {
+        EventRecord {
+            frame: event.frame(),
+            time: event.frame() as f64 / 60.0,
+            event,
+            ball: event
+                .ball_motion()
+                .map(|(position, velocity)| BallSnapshot { position, velocity }),
+            left_paddle: None,
+            right_paddle: None,
+            scorer: None,
+        }
+    }
+
+    fn serve(frame: u64) -> EventRecord {
+        record(PongEvent::Serve {
+            ball: None,
+            position: [50.0, 50.0],
+            velocity: [40.0, 20.0],
+            frame,
+        })
+    }
+
+    fn frames(log: &EventLog) -> Vec<u64> {
+        log.records().iter().map(|record| record.frame).collect()
+    }
+
+    #[test]
+    fn memory_log_drops_the_oldest_records_when_full() {
+        let log = EventLog::with_sink(Sink::memory(2));
+        for frame in 1..=3 {
+            log.push(serve(frame));
+        }
+
+        assert_eq!(vec![2, 3], frames(&log));
+    }
+
+    #[test]
+    fn memory_log_without_capacity_keeps_nothing() {
+        let log = EventLog::with_sink(Sink::memory(0));
+        log.push(serve(1));
+        log.push(serve(2));
+
+        assert!(log.is_enabled());
+        assert!(log.records().is_empty());
+    }
+
+    #[test]
+    fn file_log_turns_off_after_a_failed_write() {
+        let path = std::env::temp_dir().join(format!("pong_event_log_{}", std::process::id()));
+        File::create(&path).unwrap();
+        // Writing to a file opened for reading fails.
+        let log = EventLog::with_sink(Sink::File(LineWriter::new(File::open(&path).unwrap())));
+        log.push(serve(1));
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(!log.is_enabled());
+    }
+}
@@ -0,0 +1,127 @@
+use crate::{error::PongError, loading::GameAssets, session::Session, viewport::ArenaUi};
+use amethyst::{
+    core::timing::Time,
+    ecs::prelude::Entity,
+    input::{Button, InputEvent, VirtualKeyCode},
+    prelude::*,
+    ui::{Anchor, LineMode, UiImage, UiText, UiTransform},
+    window::Window,
+};
+
+const PANEL_COLOUR: [f32; 4] = [0.35, 0.08, 0.1, 1.0];
+
+/// Shown when the game cannot go on. The error is logged, reported to the embedding page on
+/// wasm, and shown on screen until the player quits with Escape.
+pub struct FatalError {
+    error: PongError,
+    entities: Vec<Entity>,
+}
+
+impl FatalError {
+    pub fn new(error: PongError) -> Self {
+        FatalError {
+            error,
+            entities: Vec::new(),
+        }
+    }
+}
+
+impl SimpleState for FatalError {
+    fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        let StateData { world, .. } = data;
+
+        log::error!("{}", self.error);
+        #[cfg(feature = "wasm")]
+        crate::host::report_error(&self.error.to_string());
+
+        world.write_resource::<Time>().set_time_scale(0.0);
+        // Headless runs have no window to show the error in.
+        if world.read_resource::<Session>().headless {
+            return;
+        }
+        // The title tells what went wrong even when the font is what failed to load.
+        if let Some(window) = world.try_fetch::<Window>() {
+            window.set_title(&format!("Pong - {}", self.error));
+        }
+
+        let transform = UiTransform::new(
+            "fatal_error_panel".to_string(),
+            Anchor::Middle,
+            Anchor::Middle,
+            0.,
+            0.,
+            3.,
+            480.,
+            160.,
+        );
+        let panel = world
+            .create_entity()
+            .with(ArenaUi::new(&transform, 0.))
+            .with(transform)
+            .with(UiImage::SolidColor(PANEL_COLOUR))
+            .build();
+        self.entities.push(panel);
+
+        let font = world
+            .try_fetch::<GameAssets>()
+            .map(|game_assets| game_assets.font.clone());
+        if let Some(font) = font {
+            let transform = UiTransform::new(
+                "fatal_error".to_string(),
+                Anchor::Middle,
+                Anchor::Middle,
+                0.,
+                0.,
+                4.,
+                460.,
+                140.,
+            );
+            let mut text = UiText::new(
+                font,
+                format!("{}\n\nPress Escape to quit", self.error),
+                [1.0, 1.0, 1.0, 1.0],
+                16.,
+            );
+            text.line_mode = LineMode::Wrap;
+            let message = world
+                .create_entity()
+                .with(ArenaUi::new(&transform, 16.))
+                .with(transform)
+                .with(text)
+                .build();
+            self.entities.push(message);
+        }
+    }
+
+    fn on_stop(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        let StateData { world, .. } = data;
+
+        if let Err(e) = world.delete_entities(&self.entities) {
+            log::error!("Failed to delete the error screen: {}", e);
+        }
+        self.entities.clear();
+    }
+
+    fn handle_event(
+        &mut self,
+        _data: StateData<'_, GameData<'_, '_>>,
+        event: StateEvent,
+    ) -> SimpleTrans {
+        match event {
+            StateEvent::Input(InputEvent::ButtonPressed(Button::Key(VirtualKeyCode::Escape))) => {
+                Trans::Quit
+            }
+            _ => Trans::None,
+        }
+    }
+
+    fn update(&mut self, data: &mut StateData<'_, GameData<'_, '_>>) -> SimpleTrans {
+        // Nobody is there to read the screen in a headless run.
+        if data.world.read_resource::<Session>().headless {
+            eprintln!("error: {}", self.error);
+            Trans::Quit
+        } else {
+            Trans::None
+        }
+    }
+}
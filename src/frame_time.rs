@@ -0,0 +1,84 @@
+//! Time simulated by the gameplay systems, apart from the engine's `Time`.
+
+/// Longest frame simulated, in seconds. Longer frames are slowed down to this, so that after a
+/// long gap, such as a hidden tab, the ball does not jump through a goal.
+pub const MAX_FRAME_SECONDS: f32 = 0.05;
+/// Length of a frame when running headless, in seconds.
+pub const HEADLESS_FRAME_SECONDS: f32 = 1.0 / 60.0;
+
+/// Length of the frame the gameplay systems move the game on by.
+///
+/// It follows `Time`, clamped to `MAX_FRAME_SECONDS` and scaled by the time scale, whatever the
+/// state. `Time` itself is left as the engine keeps it.
+#[derive(Debug, Default)]
+pub struct FrameTime {
+    delta_seconds: f32,
+    elapsed_seconds: f64,
+    /// Length the session gives the next frame, such as a frame of a replay.
+    next: Option<f32>,
+}
+
+impl FrameTime {
+    /// Returns the length of the frame, in seconds.
+    pub fn delta_seconds(&self) -> f32 {
+        self.delta_seconds
+    }
+
+    /// Returns the time simulated since the game started, in seconds.
+    pub fn elapsed_seconds(&self) -> f64 {
+        self.elapsed_seconds
+    }
+
+    /// Sets the length of the next frame, in place of the one `Time` measures.
+    pub fn set_next(&mut self, seconds: f32) {
+        self.next = Some(seconds);
+    }
+
+    /// Moves on to a frame of `seconds`, or of the length given to `set_next`.
+    pub fn advance(&mut self, seconds: f32, time_scale: f32) {
+        let seconds = self
+            .next
+            .take()
+            .unwrap_or_else(|| seconds.min(MAX_FRAME_SECONDS));
+        self.delta_seconds = seconds * time_scale;
+        self.elapsed_seconds += f64::from(self.delta_seconds);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn long_frames_are_clamped_and_counted_once() {
+        let mut frame_time = FrameTime::default();
+        frame_time.advance(0.01, 1.0);
+        frame_time.advance(2.0, 1.0);
+
+        assert_eq!(MAX_FRAME_SECONDS, frame_time.delta_seconds());
+        assert_eq!(
+            f64::from(0.01f32) + f64::from(MAX_FRAME_SECONDS),
+            frame_time.elapsed_seconds()
+        );
+    }
+
+    #[test]
+    fn paused_frames_move_nothing() {
+        let mut frame_time = FrameTime::default();
+        frame_time.advance(0.01, 0.0);
+
+        assert_eq!(0.0, frame_time.delta_seconds());
+        assert_eq!(0.0, frame_time.elapsed_seconds());
+    }
+
+    #[test]
+    fn the_next_frame_can_be_set_once() {
+        let mut frame_time = FrameTime::default();
+        frame_time.set_next(0.5);
+        frame_time.advance(0.01, 1.0);
+        assert_eq!(0.5, frame_time.delta_seconds());
+
+        frame_time.advance(0.01, 1.0);
+        assert_eq!(0.01, frame_time.delta_seconds());
+    }
+}
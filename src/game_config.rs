@@ -0,0 +1,242 @@
+use rand::{rngs::StdRng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::{error::PongError, layered_config::ConfigSource};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+/// Path of the game config, relative to the application root.
+pub const GAME_CONFIG_PATH: &str = "config/game.ron";
+
+/// Arena, paddle and ball settings, and the rules of a match.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GameConfig {
+    pub arena: ArenaConfig,
+    pub paddle: PaddleConfig,
+    pub ball: BallConfig,
+    pub rules: RulesConfig,
+    pub event_log: EventLogConfig,
+    pub score_ui: ScoreUiConfig,
+}
+
+impl GameConfig {
+    /// Returns the points needed to win a match, `None` when matches never end.
+    pub fn score_limit(&self) -> Option<i32> {
+        match self.rules.mode {
+            GameMode::Match => Some(self.rules.score_limit),
+            GameMode::Endless => None,
+        }
+    }
+
+    /// Returns an error for the first value the game cannot be played with, in the layer
+    /// `source` gives for its path.
+    pub fn validate(&self, source: impl Fn(&str) -> ConfigSource) -> Result<(), PongError> {
+        let invalid = |path: &str, message: String| PongError::Config {
+            name: "game",
+            origin: source(path),
+            position: None,
+            message: format!("`{}` {}", path, message),
+        };
+
+        let sizes = [
+            ("arena.width", self.arena.width),
+            ("arena.height", self.arena.height),
+            ("paddle.width", self.paddle.width),
+            ("paddle.height", self.paddle.height),
+            ("ball.radius", self.ball.radius),
+        ];
+        // Also catches NaN, which is not greater than zero either.
+        if let Some((path, size)) = sizes.iter().find(|(_, size)| !(*size > 0.0)) {
+            return Err(invalid(
+                path,
+                format!("must be greater than 0, not {}", size),
+            ));
+        }
+        if self.rules.score_limit <= 0 {
+            return Err(invalid(
+                "rules.score_limit",
+                format!("must be at least 1, not {}", self.rules.score_limit),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Size of the arena, in world units.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ArenaConfig {
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Default for ArenaConfig {
+    fn default() -> Self {
+        ArenaConfig {
+            width: 100.0,
+            height: 100.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PaddleConfig {
+    pub width: f32,
+    pub height: f32,
+    /// Speed of the paddles, in world units per second.
+    pub velocity: f32,
+}
+
+impl Default for PaddleConfig {
+    fn default() -> Self {
+        PaddleConfig {
+            width: 4.0,
+            height: 16.0,
+            velocity: 75.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BallConfig {
+    pub radius: f32,
+    /// Serve velocity, in world units per second.
+    pub velocity: [f32; 2],
+    /// Whether each serve goes up or down at random, rather than as `velocity` does.
+    pub random_serve: bool,
+}
+
+impl Default for BallConfig {
+    fn default() -> Self {
+        BallConfig {
+            radius: 2.0,
+            velocity: [75.0, 50.0],
+            random_serve: false,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RulesConfig {
+    pub mode: GameMode,
+    /// Points needed to win a match.
+    pub score_limit: i32,
+    /// Seed of the random numbers used in play, so that random serves and the aim of the
+    /// computer can be replayed. A random seed is used when `None`.
+    pub seed: Option<u64>,
+}
+
+impl Default for RulesConfig {
+    fn default() -> Self {
+        RulesConfig {
+            mode: GameMode::Match,
+            score_limit: 11,
+            seed: None,
+        }
+    }
+}
+
+/// Layout of the scores, in pixels of an arena `REFERENCE_SIZE` pixels wide, and how they show
+/// a change.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ScoreUiConfig {
+    /// Distance of each score from the middle of the arena.
+    pub spacing: f32,
+    /// Distance of the scores from the top of the arena.
+    pub top: f32,
+    pub font_size: f32,
+    /// Names shown under the scores, none when empty.
+    pub left_name: String,
+    pub right_name: String,
+    pub animation: ScoreAnimation,
+}
+
+impl Default for ScoreUiConfig {
+    fn default() -> Self {
+        ScoreUiConfig {
+            spacing: 50.0,
+            top: 50.0,
+            font_size: 50.0,
+            left_name: String::new(),
+            right_name: String::new(),
+            animation: ScoreAnimation::Pop,
+        }
+    }
+}
+
+/// How a score that went up is shown.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum ScoreAnimation {
+    Off,
+    /// The score grows, then shrinks back.
+    Pop,
+    /// The score lights up, then fades back.
+    Flash,
+}
+
+/// Whether and where the gameplay events are logged.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EventLogConfig {
+    pub output: EventLogOutput,
+}
+
+/// Where the gameplay events are logged.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum EventLogOutput {
+    Off,
+    /// The latest events are kept in memory, up to `capacity` of them.
+    Memory {
+        capacity: usize,
+    },
+    /// Every event is appended to a JSON Lines file. Natively only, the web keeps the latest
+    /// events in memory instead.
+    File {
+        path: String,
+    },
+}
+
+impl Default for EventLogOutput {
+    fn default() -> Self {
+        EventLogOutput::Off
+    }
+}
+
+/// How a match ends.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum GameMode {
+    /// The first side to reach the score limit wins.
+    Match,
+    /// Players keep scoring until they quit.
+    Endless,
+}
+
+impl Default for GameMode {
+    fn default() -> Self {
+        GameMode::Match
+    }
+}
+
+/// Random numbers used in play.
+pub struct GameRng(pub StdRng);
+
+impl Default for GameRng {
+    fn default() -> Self {
+        GameRng::new(None)
+    }
+}
+
+impl GameRng {
+    pub fn new(seed: Option<u64>) -> Self {
+        GameRng(match seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        })
+    }
+}
@@ -0,0 +1,123 @@
+#[cfg(feature = "wasm")]
+use crate::host;
+use crate::{
+    loading::GameAssets, pong::new_match, stats::MatchStats, viewport::ArenaUi, ScoreBoard, Side,
+};
+use amethyst::{
+    core::timing::Time,
+    ecs::prelude::Entity,
+    input::{Button, InputEvent, VirtualKeyCode},
+    prelude::*,
+    ui::{Anchor, LineMode, UiText, UiTransform},
+};
+
+/// Shown when a player reaches the score limit, with the stats of the match. Gameplay is paused
+/// until a new match starts.
+pub struct GameOver {
+    winner: Side,
+    entities: Vec<Entity>,
+}
+
+impl GameOver {
+    pub fn new(winner: Side) -> Self {
+        GameOver {
+            winner,
+            entities: Vec::new(),
+        }
+    }
+}
+
+impl SimpleState for GameOver {
+    fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        let StateData { world, .. } = data;
+
+        world.write_resource::<Time>().set_time_scale(0.0);
+
+        let font = world.read_resource::<GameAssets>().font.clone();
+        let winner = match self.winner {
+            Side::Left => "Left",
+            Side::Right => "Right",
+        };
+        let transform = UiTransform::new(
+            "game_over".to_string(),
+            Anchor::Middle,
+            Anchor::Middle,
+            0.,
+            0.,
+            2.,
+            600.,
+            40.,
+        );
+        let message = world
+            .create_entity()
+            .with(ArenaUi::new(&transform, 24.))
+            .with(transform)
+            .with(UiText::new(
+                font.clone(),
+                format!("{} player wins! Press Space to play again", winner),
+                [1.0, 1.0, 1.0, 1.0],
+                24.,
+            ))
+            .build();
+        self.entities.push(message);
+
+        let summary = world.read_resource::<MatchStats>().to_string();
+        let transform = UiTransform::new(
+            "game_over_stats".to_string(),
+            Anchor::Middle,
+            Anchor::Middle,
+            0.,
+            -90.,
+            2.,
+            500.,
+            120.,
+        );
+        let mut text = UiText::new(font, summary, [1.0, 1.0, 1.0, 1.0], 14.);
+        text.line_mode = LineMode::Wrap;
+        let stats = world
+            .create_entity()
+            .with(ArenaUi::new(&transform, 14.))
+            .with(transform)
+            .with(text)
+            .build();
+        self.entities.push(stats);
+    }
+
+    fn on_stop(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        let StateData { world, .. } = data;
+
+        if let Err(e) = world.delete_entities(&self.entities) {
+            log::error!("Failed to delete the game over message: {}", e);
+        }
+        self.entities.clear();
+
+        world.write_resource::<Time>().set_time_scale(1.0);
+    }
+
+    fn handle_event(
+        &mut self,
+        data: StateData<'_, GameData<'_, '_>>,
+        event: StateEvent,
+    ) -> SimpleTrans {
+        let StateData { world, .. } = data;
+
+        match event {
+            StateEvent::Input(InputEvent::ButtonPressed(Button::Key(VirtualKeyCode::Space))) => {
+                new_match(world);
+                Trans::Pop
+            }
+            _ => Trans::None,
+        }
+    }
+
+    fn update(&mut self, data: &mut StateData<'_, GameData<'_, '_>>) -> SimpleTrans {
+        // The embedding page may start a new match. Pausing and resuming wait for the match.
+        #[cfg(feature = "wasm")]
+        host::apply_commands(data.world, &[]);
+        if data.world.read_resource::<ScoreBoard>().winner().is_none() {
+            Trans::Pop
+        } else {
+            Trans::None
+        }
+    }
+}
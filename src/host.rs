@@ -0,0 +1,312 @@
+//! Control of the game by the page embedding it.
+
+use amethyst::ecs::{Join, World, WorldExt};
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+
+use crate::{event_log::BallSnapshot, stats::MatchStats, Paddle, PaddleControl, Side};
+
+/// A request from the embedding page.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HostCommand {
+    Pause,
+    Resume,
+    /// Starts a new match.
+    Reset,
+    SetPaddleControl(Side, PaddleControl),
+    /// The page was hidden or lost focus.
+    FocusLost,
+}
+
+/// State of the game, as read by the embedding page.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct GameSnapshot {
+    pub score_left: i32,
+    pub score_right: i32,
+    pub winner: Option<Side>,
+    pub paused: bool,
+    /// The ball, `None` before the first serve.
+    pub ball: Option<BallSnapshot>,
+    pub stats: MatchStats,
+}
+
+/// Commands and state shared with the embedding page.
+#[derive(Clone, Default)]
+pub struct HostLink {
+    shared: Arc<Mutex<Shared>>,
+}
+
+#[derive(Default)]
+struct Shared {
+    commands: Vec<HostCommand>,
+    snapshot: GameSnapshot,
+}
+
+impl HostLink {
+    /// Queues a command for the game to handle on its next frame.
+    pub fn send(&self, command: HostCommand) {
+        if let Ok(mut shared) = self.shared.lock() {
+            shared.commands.push(command);
+        }
+    }
+
+    /// Returns the commands sent since the last call.
+    pub fn take_commands(&self) -> Vec<HostCommand> {
+        self.shared
+            .lock()
+            .map(|mut shared| std::mem::take(&mut shared.commands))
+            .unwrap_or_default()
+    }
+
+    /// Drops the queued commands equal to `command`.
+    pub fn discard(&self, command: HostCommand) {
+        if let Ok(mut shared) = self.shared.lock() {
+            shared.commands.retain(|queued| *queued != command);
+        }
+    }
+
+    /// Puts commands back in front of the ones sent since, for a later frame to handle.
+    pub fn requeue(&self, commands: Vec<HostCommand>) {
+        if let Ok(mut shared) = self.shared.lock() {
+            shared.commands.splice(0..0, commands);
+        }
+    }
+
+    pub fn snapshot(&self) -> GameSnapshot {
+        self.shared
+            .lock()
+            .map(|shared| shared.snapshot.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn set_snapshot(&self, snapshot: GameSnapshot) {
+        if let Ok(mut shared) = self.shared.lock() {
+            shared.snapshot = snapshot;
+        }
+    }
+}
+
+/// Applies the commands sent by the embedding page that do not depend on the current state, and
+/// returns the first of the pause, resume and focus commands that the state `handles`.
+///
+/// The commands after it stay queued for the state it leads to. Pause and resume commands the
+/// state does not handle also stay queued, until a state handles them, while focus commands the
+/// state does not handle are stale and dropped.
+pub fn apply_commands(world: &mut World, handles: &[HostCommand]) -> Option<HostCommand> {
+    let host_link = match world.try_fetch::<HostLink>() {
+        Some(host_link) => host_link.clone(),
+        None => return None,
+    };
+
+    let mut handled = None;
+    let mut queued = Vec::new();
+    for command in host_link.take_commands() {
+        match command {
+            HostCommand::Reset => crate::pong::new_match(world),
+            HostCommand::SetPaddleControl(side, control) => {
+                for paddle in (&mut world.write_storage::<Paddle>()).join() {
+                    if paddle.side == side {
+                        paddle.control = control;
+                    }
+                }
+            }
+            _ if handled.is_some() => queued.push(command),
+            _ if handles.contains(&command) => handled = Some(command),
+            HostCommand::Pause | HostCommand::Resume => queued.push(command),
+            HostCommand::FocusLost => {}
+        }
+    }
+    host_link.requeue(queued);
+    handled
+}
+
+#[cfg(feature = "wasm")]
+pub use self::wasm::{dispatch_event, listen_to_focus, report_error, PongHandle};
+
+#[cfg(feature = "wasm")]
+mod wasm {
+    use js_sys::Function;
+    use std::{cell::RefCell, collections::HashMap};
+    use wasm_bindgen::{prelude::*, JsCast};
+
+    use super::{HostCommand, HostLink};
+    use crate::{
+        event::{PongEvent, PongEventKind},
+        event_log::EventLog,
+        PaddleControl, Side,
+    };
+
+    thread_local! {
+        /// Callbacks registered by the page, by event. Events are dispatched on the main thread.
+        static LISTENERS: RefCell<HashMap<PongEventKind, Vec<Function>>> = RefCell::default();
+        /// Callbacks registered by the page for errors that stop the game.
+        static ERROR_LISTENERS: RefCell<Vec<Function>> = RefCell::default();
+    }
+
+    /// Handle to a running game, returned by `PongAppBuilder.run()`.
+    #[wasm_bindgen]
+    pub struct PongHandle {
+        host_link: HostLink,
+        event_log: EventLog,
+    }
+
+    impl PongHandle {
+        pub fn new(host_link: HostLink, event_log: EventLog) -> Self {
+            PongHandle {
+                host_link,
+                event_log,
+            }
+        }
+    }
+
+    #[wasm_bindgen]
+    impl PongHandle {
+        /// Pauses the game until `resume()` is called.
+        pub fn pause(&self) {
+            self.host_link.send(HostCommand::Pause);
+        }
+
+        pub fn resume(&self) {
+            self.host_link.send(HostCommand::Resume);
+        }
+
+        /// Resets the scores and serves the ball for a new match.
+        pub fn reset(&self) {
+            self.host_link.send(HostCommand::Reset);
+        }
+
+        /// Sets whether a paddle follows its input axis or the pointer.
+        pub fn set_paddle_controller(&self, side: Side, kind: PaddleControl) {
+            self.host_link
+                .send(HostCommand::SetPaddleControl(side, kind));
+        }
+
+        /// Returns the scores, the winner, whether the game is paused, the ball position and
+        /// velocity, and the stats of the match, as of the last frame.
+        pub fn state(&self) -> Result<JsValue, JsValue> {
+            JsValue::from_serde(&self.host_link.snapshot())
+                .map_err(|e| js_sys::Error::new(&e.to_string()).into())
+        }
+
+        /// Returns the stats of the match in play as JSON, as of the last frame.
+        pub fn stats(&self) -> Result<String, JsValue> {
+            self.host_link
+                .snapshot()
+                .stats
+                .to_json()
+                .map_err(|e| js_sys::Error::new(&e.to_string()).into())
+        }
+
+        /// Returns the events kept in memory by the event log, oldest first, with the frame,
+        /// the time, the ball and paddles, and who scored.
+        pub fn event_log(&self) -> Result<JsValue, JsValue> {
+            JsValue::from_serde(&self.event_log.records())
+                .map_err(|e| js_sys::Error::new(&e.to_string()).into())
+        }
+
+        /// Calls `callback` with the event data each time the event happens, such as the surface
+        /// the ball bounced off or the side that scored, the position and velocity of the ball,
+        /// and the frame.
+        ///
+        /// Events are `"bounce"`, `"score"`, `"serve"` and `"match-won"`, and `"error"`, which is
+        /// called with an `Error` when the game cannot go on.
+        pub fn on(&self, event: &str, callback: Function) -> Result<(), JsValue> {
+            let kind = match event {
+                "error" => {
+                    ERROR_LISTENERS.with(|listeners| listeners.borrow_mut().push(callback));
+                    return Ok(());
+                }
+                "bounce" => PongEventKind::Bounce,
+                "score" => PongEventKind::Score,
+                "serve" => PongEventKind::Serve,
+                "match-won" => PongEventKind::GameOver,
+                _ => {
+                    return Err(js_sys::Error::new(&format!("Unknown event `{}`", event)).into());
+                }
+            };
+            LISTENERS.with(|listeners| {
+                listeners
+                    .borrow_mut()
+                    .entry(kind)
+                    .or_default()
+                    .push(callback)
+            });
+            Ok(())
+        }
+    }
+
+    /// Sends `FocusLost` when the tab is hidden or the window loses focus.
+    pub fn listen_to_focus(host_link: &HostLink) -> Result<(), JsValue> {
+        let window = web_sys::window().ok_or_else(|| JsValue::from_str("no window"))?;
+        let document = window
+            .document()
+            .ok_or_else(|| JsValue::from_str("no document"))?;
+
+        let on_visibility_change = {
+            let host_link = host_link.clone();
+            let document = document.clone();
+            Closure::wrap(Box::new(move || {
+                if document.hidden() {
+                    host_link.send(HostCommand::FocusLost);
+                }
+            }) as Box<dyn FnMut()>)
+        };
+        document.add_event_listener_with_callback(
+            "visibilitychange",
+            on_visibility_change.as_ref().unchecked_ref(),
+        )?;
+
+        let on_blur = {
+            let host_link = host_link.clone();
+            Closure::wrap(
+                Box::new(move || host_link.send(HostCommand::FocusLost)) as Box<dyn FnMut()>
+            )
+        };
+        window.add_event_listener_with_callback("blur", on_blur.as_ref().unchecked_ref())?;
+
+        // The listeners live as long as the page.
+        on_visibility_change.forget();
+        on_blur.forget();
+
+        Ok(())
+    }
+
+    /// Calls the callbacks registered for errors with a JS `Error`.
+    pub fn report_error(message: &str) {
+        let callbacks = ERROR_LISTENERS.with(|listeners| listeners.borrow().clone());
+        let error = JsValue::from(js_sys::Error::new(message));
+        for callback in callbacks {
+            if let Err(e) = callback.call1(&JsValue::NULL, &error) {
+                log::error!("Error callback failed: {:?}", e);
+            }
+        }
+    }
+
+    /// Calls the callbacks registered for the event.
+    pub fn dispatch_event(event: &PongEvent) {
+        // The callbacks are cloned, so that they can register more callbacks.
+        let callbacks = LISTENERS.with(|listeners| {
+            listeners
+                .borrow()
+                .get(&event.kind())
+                .cloned()
+                .unwrap_or_default()
+        });
+        if callbacks.is_empty() {
+            return;
+        }
+
+        let data = match JsValue::from_serde(event) {
+            Ok(data) => data,
+            Err(e) => {
+                log::error!("Failed to convert event {:?}: {}", event, e);
+                return;
+            }
+        };
+        for callback in callbacks {
+            if let Err(e) = callback.call1(&JsValue::NULL, &data) {
+                log::error!("Event callback failed: {:?}", e);
+            }
+        }
+    }
+}
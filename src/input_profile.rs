@@ -0,0 +1,217 @@
+//! Shaping of raw paddle axis values before they move the paddles.
+
+use serde::{Deserialize, Serialize};
+
+use crate::Side;
+
+/// How a raw axis value is turned into paddle movement.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct InputProfile {
+    /// Multiplier applied after the response curve.
+    pub sensitivity: f32,
+    /// Exponent of the response curve. Values above `1.0` give finer control near the centre.
+    pub exponent: f32,
+    /// Raw magnitude below which the axis reads as `0.0`.
+    pub dead_zone: f32,
+    /// Raw magnitude distance from `1.0` above which the axis reads as fully pushed.
+    pub outer_dead_zone: f32,
+    /// Time in seconds for the output to catch up with the input. `0.0` disables smoothing.
+    pub smoothing: f32,
+    /// Whether the axis direction is flipped.
+    pub invert: bool,
+}
+
+impl Default for InputProfile {
+    fn default() -> Self {
+        InputProfile {
+            sensitivity: 1.0,
+            exponent: 1.0,
+            dead_zone: 0.0,
+            outer_dead_zone: 0.0,
+            smoothing: 0.0,
+            invert: false,
+        }
+    }
+}
+
+impl InputProfile {
+    /// Returns the shaped value of a raw axis value, within `-1.0..=1.0`.
+    pub fn response(&self, raw: f32) -> f32 {
+        let magnitude = raw.abs().min(1.0);
+        if magnitude <= self.dead_zone {
+            return 0.0;
+        }
+
+        // Rescale the live range between the dead zones to `0.0..=1.0`.
+        let live_range = (1.0 - self.dead_zone - self.outer_dead_zone).max(std::f32::EPSILON);
+        let scaled = ((magnitude - self.dead_zone) / live_range).min(1.0);
+        let curved = (scaled.powf(self.exponent.max(std::f32::EPSILON)) * self.sensitivity)
+            .max(0.0)
+            .min(1.0);
+
+        let sign = if self.invert {
+            -raw.signum()
+        } else {
+            raw.signum()
+        };
+        sign * curved
+    }
+
+    /// Returns the value after moving from `previous` towards `target` over `delta_seconds`.
+    pub fn smooth(&self, previous: f32, target: f32, delta_seconds: f32) -> f32 {
+        if self.smoothing <= 0.0 {
+            return target;
+        }
+
+        let blend = 1.0 - (-delta_seconds / self.smoothing).exp();
+        previous + (target - previous) * blend
+    }
+}
+
+/// Input profile for each paddle.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct InputProfiles {
+    pub left: InputProfile,
+    pub right: InputProfile,
+}
+
+impl InputProfiles {
+    /// Returns the profile of the paddle on the given side.
+    pub fn get(&self, side: Side) -> &InputProfile {
+        match side {
+            Side::Left => &self.left,
+            Side::Right => &self.right,
+        }
+    }
+}
+
+/// Shaped axis values for each paddle, `None` when the axis is not bound.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PaddleAxisValues {
+    pub left: Option<f32>,
+    pub right: Option<f32>,
+}
+
+impl PaddleAxisValues {
+    /// Returns the shaped axis value of the paddle on the given side.
+    pub fn get(&self, side: Side) -> Option<f32> {
+        match side {
+            Side::Left => self.left,
+            Side::Right => self.right,
+        }
+    }
+
+    /// Returns a mutable reference to the axis value of the paddle on the given side.
+    pub fn get_mut(&mut self, side: Side) -> &mut Option<f32> {
+        match side {
+            Side::Left => &mut self.left,
+            Side::Right => &mut self.right,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!(
+            (actual - expected).abs() < 1e-5,
+            "expected {}, got {}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn default_profile_passes_values_through() {
+        let profile = InputProfile::default();
+        for &raw in &[-1.0, -0.5, 0.0, 0.25, 1.0] {
+            assert_close(profile.response(raw), raw);
+        }
+        assert_close(profile.response(3.0), 1.0);
+    }
+
+    #[test]
+    fn inner_dead_zone_reads_as_zero_and_rescales_the_rest() {
+        let profile = InputProfile {
+            dead_zone: 0.2,
+            ..Default::default()
+        };
+        assert_close(profile.response(0.1), 0.0);
+        assert_close(profile.response(-0.2), 0.0);
+        assert_close(profile.response(0.6), 0.5);
+        assert_close(profile.response(-1.0), -1.0);
+    }
+
+    #[test]
+    fn outer_dead_zone_reads_as_fully_pushed() {
+        let profile = InputProfile {
+            outer_dead_zone: 0.2,
+            ..Default::default()
+        };
+        assert_close(profile.response(0.4), 0.5);
+        assert_close(profile.response(0.8), 1.0);
+        assert_close(profile.response(-0.9), -1.0);
+    }
+
+    #[test]
+    fn exponent_curves_the_response() {
+        let profile = InputProfile {
+            exponent: 2.0,
+            ..Default::default()
+        };
+        assert_close(profile.response(0.5), 0.25);
+        assert_close(profile.response(-0.5), -0.25);
+        assert_close(profile.response(1.0), 1.0);
+    }
+
+    #[test]
+    fn invert_flips_the_direction() {
+        let profile = InputProfile {
+            invert: true,
+            ..Default::default()
+        };
+        assert_close(profile.response(0.5), -0.5);
+        assert_close(profile.response(-1.0), 1.0);
+    }
+
+    #[test]
+    fn sensitivity_scales_the_response_within_range() {
+        let profile = InputProfile {
+            sensitivity: 2.0,
+            ..Default::default()
+        };
+        assert_close(profile.response(0.25), 0.5);
+        assert_close(profile.response(0.75), 1.0);
+        assert_close(profile.response(-0.75), -1.0);
+    }
+
+    #[test]
+    fn smoothing_converges_on_the_target() {
+        let profile = InputProfile {
+            smoothing: 0.1,
+            ..Default::default()
+        };
+        let mut value = 0.0;
+        let mut previous_gap = 1.0;
+        for _ in 0..60 {
+            value = profile.smooth(value, 1.0, 1.0 / 60.0);
+            let gap = 1.0 - value;
+            assert!(gap > 0.0 && gap < previous_gap);
+            previous_gap = gap;
+        }
+        // One second is ten times the smoothing time.
+        assert!(previous_gap < 1e-4);
+
+        // The smoothing time is the time constant of the approach.
+        assert_close(profile.smooth(0.0, 1.0, 0.1), 1.0 - (-1.0f32).exp());
+    }
+
+    #[test]
+    fn no_smoothing_jumps_to_the_target() {
+        assert_close(InputProfile::default().smooth(0.0, 0.7, 1.0 / 60.0), 0.7);
+    }
+}
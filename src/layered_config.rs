@@ -0,0 +1,637 @@
+//! Configuration assembled from layers, each overriding the values it sets:
+//!
+//! 1. the built in defaults,
+//! 2. the RON file, such as `config/game.ron`, or the text given by the embedding page on wasm,
+//! 3. the user override file next to it, such as `config/game.user.ron`,
+//! 4. environment variables such as `PONG_GAME__BALL__RADIUS=3.0`, holding a RON value.
+//!
+//! Fields of structs are merged one by one. Any other value, such as a list, a map or an enum,
+//! is replaced as a whole.
+//!
+//! Each layer is deserialized over the value of the layers under it, so a layer only has to
+//! hold the fields it changes, and an invalid layer is reported on its own.
+
+use crate::error::PongError;
+use serde::{
+    de::{
+        self, DeserializeOwned, DeserializeSeed, Deserializer, IntoDeserializer, MapAccess, Visitor,
+    },
+    Serialize,
+};
+use serde_json::{Map, Value};
+#[cfg(not(feature = "wasm"))]
+use std::path::{Path, PathBuf};
+use std::{cell::RefCell, fmt};
+
+/// Prefix of the environment variables overriding config values.
+const ENV_PREFIX: &str = "PONG_";
+/// Separator of the config name and field names in environment variables.
+const ENV_SEPARATOR: &str = "__";
+
+/// Where a config value comes from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConfigSource {
+    /// The built in default.
+    Default,
+    /// The RON file or the user override file.
+    #[cfg(not(feature = "wasm"))]
+    File(PathBuf),
+    /// Text given by the embedding page.
+    #[cfg(feature = "wasm")]
+    Page,
+    /// An environment variable, by name.
+    Env(String),
+}
+
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigSource::Default => f.write_str("default"),
+            #[cfg(not(feature = "wasm"))]
+            ConfigSource::File(path) => write!(f, "{}", path.display()),
+            #[cfg(feature = "wasm")]
+            ConfigSource::Page => f.write_str("page"),
+            ConfigSource::Env(name) => write!(f, "${}", name),
+        }
+    }
+}
+
+/// A loaded config, with the source of each of its values.
+#[derive(Debug)]
+pub struct LayeredConfig<T> {
+    pub value: T,
+    /// Path, value and source of each value, in the order of the fields.
+    sources: Vec<(String, String, ConfigSource)>,
+}
+
+impl<T> LayeredConfig<T> {
+    /// Returns one line per value, giving its path, value and source.
+    pub fn report(&self) -> String {
+        self.sources
+            .iter()
+            .map(|(path, value, source)| format!("{} = {} ({})\n", path, value, source))
+            .collect()
+    }
+
+    /// Returns the layer that set the value at `path`, such as `arena.width`.
+    pub fn source(&self, path: &str) -> ConfigSource {
+        self.sources
+            .iter()
+            .find(|(known, ..)| known == path)
+            .map(|(.., source)| source.clone())
+            .unwrap_or(ConfigSource::Default)
+    }
+}
+
+/// The RON file of a config natively, or the RON text given by the embedding page on wasm.
+#[cfg(not(feature = "wasm"))]
+type ConfigFile = PathBuf;
+#[cfg(feature = "wasm")]
+type ConfigFile = String;
+
+/// Loads one config from its layers.
+#[derive(Clone, Debug)]
+pub struct ConfigLoader {
+    /// Name of the config in environment variables, such as `game` for `PONG_GAME__*`.
+    name: &'static str,
+    file: Option<ConfigFile>,
+}
+
+impl ConfigLoader {
+    pub fn new(name: &'static str) -> Self {
+        ConfigLoader { name, file: None }
+    }
+
+    /// Loads the config, merging its layers over the defaults.
+    ///
+    /// The RON file must exist, while the user override file is optional.
+    pub fn load<T>(&self) -> Result<LayeredConfig<T>, PongError>
+    where
+        T: Default + Serialize + DeserializeOwned,
+    {
+        let defaults =
+            ron::ser::to_string(&T::default()).map_err(|e| self.error(ConfigSource::Default, e))?;
+        let mut layers = vec![(defaults, ConfigSource::Default)];
+        layers.extend(self.file_layers()?);
+        layers.extend(self.env_layers());
+        self.merge(layers)
+    }
+
+    /// Deserializes each layer over the ones before it.
+    fn merge<T>(&self, layers: Vec<(String, ConfigSource)>) -> Result<LayeredConfig<T>, PongError>
+    where
+        T: Default + Serialize + DeserializeOwned,
+    {
+        let mut value = T::default();
+        let mut below = Value::Null;
+        let mut paths = Vec::<(String, ConfigSource)>::new();
+        for (text, source) in layers {
+            let set = RefCell::new(Vec::new());
+            value = ron::de::Deserializer::from_str(&text)
+                .and_then(|mut layer| {
+                    let value = deserialize_over(&mut layer, below.take(), &set)?;
+                    layer.end()?;
+                    Ok(value)
+                })
+                .map_err(|e| match source {
+                    // The position would be in the text wrapping the value of the variable.
+                    ConfigSource::Env(_) => self.error(source.clone(), e),
+                    _ => PongError::ron(self.name, source.clone(), e),
+                })?;
+            below = serde_json::to_value(&value).map_err(|e| self.error(source.clone(), e))?;
+
+            for path in set.into_inner() {
+                match paths.iter_mut().find(|(known, _)| *known == path) {
+                    Some((_, known_source)) => *known_source = source.clone(),
+                    None => paths.push((path, source.clone())),
+                }
+            }
+        }
+
+        // Values the layers set but the config ignores have no value to report.
+        let sources = paths
+            .into_iter()
+            .filter_map(|(path, source)| {
+                let value = path
+                    .split('.')
+                    .filter(|field| !field.is_empty())
+                    .try_fold(&below, |value, field| value.get(field))?;
+                Some((path, value.to_string(), source))
+            })
+            .collect();
+        Ok(LayeredConfig { value, sources })
+    }
+
+    /// Returns the layers of the environment variables overriding this config, in name order,
+    /// each holding its value nested in the structs of its path.
+    fn env_layers(&self) -> Vec<(String, ConfigSource)> {
+        let prefix = format!(
+            "{}{}{}",
+            ENV_PREFIX,
+            self.name.to_uppercase(),
+            ENV_SEPARATOR
+        );
+        let mut vars = std::env::vars()
+            .filter(|(name, _)| name.starts_with(&prefix) && name.len() > prefix.len())
+            .collect::<Vec<_>>();
+        vars.sort();
+        vars.into_iter()
+            .map(|(name, value)| {
+                let text = name[prefix.len()..]
+                    .rsplit(ENV_SEPARATOR)
+                    .fold(value, |text, field| {
+                        format!("({}:{})", field.to_lowercase(), text)
+                    });
+                (text, ConfigSource::Env(name))
+            })
+            .collect()
+    }
+
+    fn error(&self, origin: ConfigSource, e: impl fmt::Display) -> PongError {
+        PongError::Config {
+            name: self.name,
+            origin,
+            position: None,
+            message: e.to_string(),
+        }
+    }
+}
+
+#[cfg(not(feature = "wasm"))]
+impl ConfigLoader {
+    /// Reads the config from `path`, and the user override file next to it.
+    pub fn with_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.file = Some(path.into());
+        self
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Returns the files the config is read from, whether they exist or not.
+    pub fn files(&self) -> Vec<PathBuf> {
+        self.file
+            .iter()
+            .flat_map(|path| vec![path.clone(), user_path(path)])
+            .collect()
+    }
+
+    fn file_layers(&self) -> Result<Vec<(String, ConfigSource)>, PongError> {
+        let mut layers = Vec::new();
+        if let Some(path) = self.file.as_ref() {
+            layers.push((self.read(path)?, ConfigSource::File(path.clone())));
+            let user_path = user_path(path);
+            if user_path.exists() {
+                layers.push((self.read(&user_path)?, ConfigSource::File(user_path)));
+            }
+        }
+        Ok(layers)
+    }
+
+    fn read(&self, path: &Path) -> Result<String, PongError> {
+        std::fs::read_to_string(path).or_else(|e| {
+            embedded(path).ok_or_else(|| self.error(ConfigSource::File(path.to_path_buf()), e))
+        })
+    }
+}
+
+/// Returns the copy of a config file compiled into the binary, read in place of the file when it
+/// is missing.
+#[cfg(all(not(feature = "wasm"), feature = "embedded-assets"))]
+fn embedded(path: &Path) -> Option<String> {
+    let file_name = path.file_name()?.to_str()?;
+    crate::embedded_assets::config(file_name).map(str::to_string)
+}
+
+#[cfg(all(not(feature = "wasm"), not(feature = "embedded-assets")))]
+fn embedded(_path: &Path) -> Option<String> {
+    None
+}
+
+#[cfg(feature = "wasm")]
+impl ConfigLoader {
+    /// Reads the config from RON text given by the embedding page.
+    pub fn with_text(mut self, text: impl Into<String>) -> Self {
+        self.file = Some(text.into());
+        self
+    }
+
+    fn file_layers(&self) -> Result<Vec<(String, ConfigSource)>, PongError> {
+        Ok(self
+            .file
+            .iter()
+            .map(|text| (text.clone(), ConfigSource::Page))
+            .collect())
+    }
+}
+
+/// Returns the user override file of the config at `path`, such as `game.user.ron` for
+/// `game.ron`.
+#[cfg(not(feature = "wasm"))]
+fn user_path(path: &Path) -> PathBuf {
+    path.with_extension("user.ron")
+}
+
+/// Deserializes a layer over `below`, the value of the layers under it, adding the path of each
+/// value the layer sets to `set`.
+fn deserialize_over<'de, T, D>(
+    layer: D,
+    below: Value,
+    set: &RefCell<Vec<String>>,
+) -> Result<T, D::Error>
+where
+    T: DeserializeOwned,
+    D: Deserializer<'de>,
+{
+    T::deserialize(Over {
+        layer,
+        below,
+        path: String::new(),
+        set,
+    })
+}
+
+/// Deserializer of a value of a layer, merging the fields of structs with `below`.
+struct Over<'s, D> {
+    layer: D,
+    below: Value,
+    path: String,
+    set: &'s RefCell<Vec<String>>,
+}
+
+/// Forwards deserializing a value that is replaced as a whole to the layer, recording its path.
+macro_rules! replace_whole {
+    ($($method:ident($($arg:ident: $arg_type:ty),*);)*) => {
+        $(
+            fn $method<V>(self, $($arg: $arg_type,)* visitor: V) -> Result<V::Value, D::Error>
+            where
+                V: Visitor<'de>,
+            {
+                self.set.borrow_mut().push(self.path);
+                self.layer.$method($($arg,)* visitor)
+            }
+        )*
+    };
+}
+
+impl<'de, 's, D: Deserializer<'de>> Deserializer<'de> for Over<'s, D> {
+    type Error = D::Error;
+
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, D::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let below = match self.below {
+            Value::Object(below) => below,
+            _ => Map::new(),
+        };
+        self.layer.deserialize_struct(
+            name,
+            fields,
+            StructOver {
+                visitor,
+                below,
+                path: self.path,
+                set: self.set,
+            },
+        )
+    }
+
+    replace_whole! {
+        deserialize_any();
+        deserialize_bool();
+        deserialize_i8();
+        deserialize_i16();
+        deserialize_i32();
+        deserialize_i64();
+        deserialize_u8();
+        deserialize_u16();
+        deserialize_u32();
+        deserialize_u64();
+        deserialize_f32();
+        deserialize_f64();
+        deserialize_char();
+        deserialize_str();
+        deserialize_string();
+        deserialize_bytes();
+        deserialize_byte_buf();
+        deserialize_option();
+        deserialize_unit();
+        deserialize_unit_struct(name: &'static str);
+        deserialize_newtype_struct(name: &'static str);
+        deserialize_seq();
+        deserialize_tuple(len: usize);
+        deserialize_tuple_struct(name: &'static str, len: usize);
+        deserialize_map();
+        deserialize_enum(name: &'static str, variants: &'static [&'static str]);
+        deserialize_identifier();
+        deserialize_ignored_any();
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.layer.is_human_readable()
+    }
+}
+
+/// Visitor of a struct of a layer, giving the fields the layer leaves out from `below`.
+struct StructOver<'s, V> {
+    visitor: V,
+    below: Map<String, Value>,
+    path: String,
+    set: &'s RefCell<Vec<String>>,
+}
+
+impl<'de, 's, V: Visitor<'de>> Visitor<'de> for StructOver<'s, V> {
+    type Value = V::Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.visitor.expecting(f)
+    }
+
+    fn visit_map<A>(self, layer: A) -> Result<V::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        self.visitor.visit_map(FieldsOver {
+            layer: Some(layer),
+            below: self.below,
+            field: None,
+            path: self.path,
+            set: self.set,
+        })
+    }
+}
+
+/// Where the value of the current field comes from.
+enum FieldValue {
+    /// The layer, over the value below it if any.
+    Layer(String, Value),
+    /// Only the layers below.
+    Below(Value),
+}
+
+/// Fields of a struct of a layer, followed by the fields it leaves out from `below`.
+struct FieldsOver<'s, A> {
+    /// The fields of the layer, until they run out.
+    layer: Option<A>,
+    below: Map<String, Value>,
+    field: Option<FieldValue>,
+    path: String,
+    set: &'s RefCell<Vec<String>>,
+}
+
+impl<'de, 's, A: MapAccess<'de>> MapAccess<'de> for FieldsOver<'s, A> {
+    type Error = A::Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, A::Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        if let Some(layer) = self.layer.as_mut() {
+            match layer.next_key::<String>()? {
+                Some(field) => {
+                    let below = self.below.remove(&field).unwrap_or(Value::Null);
+                    self.field = Some(FieldValue::Layer(field.clone(), below));
+                    return seed.deserialize(field.into_deserializer()).map(Some);
+                }
+                None => self.layer = None,
+            }
+        }
+
+        let field = match self.below.keys().next() {
+            Some(field) => field.clone(),
+            None => return Ok(None),
+        };
+        let below = self.below.remove(&field).unwrap_or(Value::Null);
+        self.field = Some(FieldValue::Below(below));
+        seed.deserialize(field.into_deserializer()).map(Some)
+    }
+
+    fn next_value_seed<S>(&mut self, seed: S) -> Result<S::Value, A::Error>
+    where
+        S: DeserializeSeed<'de>,
+    {
+        match self
+            .field
+            .take()
+            .expect("next_key_seed has not been called")
+        {
+            FieldValue::Layer(field, below) => {
+                let path = if self.path.is_empty() {
+                    field
+                } else {
+                    format!("{}.{}", self.path, field)
+                };
+                let layer = self.layer.as_mut().expect("the layer has no more fields");
+                layer.next_value_seed(SeedOver {
+                    seed,
+                    below,
+                    path,
+                    set: self.set,
+                })
+            }
+            FieldValue::Below(below) => seed.deserialize(below).map_err(de::Error::custom),
+        }
+    }
+}
+
+/// Seed of the value of a field of a layer, deserializing it over `below`.
+struct SeedOver<'s, S> {
+    seed: S,
+    below: Value,
+    path: String,
+    set: &'s RefCell<Vec<String>>,
+}
+
+impl<'de, 's, S: DeserializeSeed<'de>> DeserializeSeed<'de> for SeedOver<'s, S> {
+    type Value = S::Value;
+
+    fn deserialize<D>(self, layer: D) -> Result<S::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        self.seed.deserialize(Over {
+            layer,
+            below: self.below,
+            path: self.path,
+            set: self.set,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, Default, PartialEq, Deserialize, Serialize)]
+    #[serde(deny_unknown_fields)]
+    struct Outer {
+        inner: Inner,
+        choice: Choice,
+        list: Vec<u32>,
+    }
+
+    #[derive(Debug, Default, PartialEq, Deserialize, Serialize)]
+    #[serde(deny_unknown_fields)]
+    struct Inner {
+        a: u32,
+        b: f32,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    enum Choice {
+        Off,
+        On(u32),
+        Named { x: i32, y: i32 },
+    }
+
+    impl Default for Choice {
+        fn default() -> Self {
+            Choice::Off
+        }
+    }
+
+    fn file(name: &str) -> ConfigSource {
+        ConfigSource::File(PathBuf::from(name))
+    }
+
+    fn merge(layers: &[(&str, ConfigSource)]) -> Result<LayeredConfig<Outer>, PongError> {
+        let defaults = ron::ser::to_string(&Outer::default()).unwrap();
+        let mut all = vec![(defaults, ConfigSource::Default)];
+        all.extend(
+            layers
+                .iter()
+                .map(|(text, source)| (text.to_string(), source.clone())),
+        );
+        ConfigLoader::new("test").merge(all)
+    }
+
+    #[test]
+    fn merges_struct_fields_one_by_one() {
+        let config = merge(&[
+            ("(inner: (a: 1, b: 2.0), list: [1])", file("test.ron")),
+            ("(inner: (b: 3.0))", file("test.user.ron")),
+        ])
+        .unwrap();
+
+        assert_eq!(
+            Outer {
+                inner: Inner { a: 1, b: 3.0 },
+                choice: Choice::Off,
+                list: vec![1],
+            },
+            config.value
+        );
+    }
+
+    #[test]
+    fn replaces_enums_and_lists_as_a_whole() {
+        let config = merge(&[
+            (
+                "(choice: Named(x: 1, y: 2), list: [1, 2])",
+                file("test.ron"),
+            ),
+            ("(choice: On(3), list: [4])", file("test.user.ron")),
+        ])
+        .unwrap();
+
+        assert_eq!(Choice::On(3), config.value.choice);
+        assert_eq!(vec![4], config.value.list);
+    }
+
+    #[test]
+    fn reports_the_layer_setting_each_value() {
+        let config = merge(&[
+            ("(inner: (a: 1), choice: On(2))", file("test.ron")),
+            ("(inner: (a: 3))", file("test.user.ron")),
+        ])
+        .unwrap();
+
+        assert_eq!(
+            "inner.a = 3 (test.user.ron)\n\
+             inner.b = 0.0 (default)\n\
+             choice = {\"On\":2} (test.ron)\n\
+             list = [] (default)\n",
+            config.report()
+        );
+        assert_eq!(file("test.user.ron"), config.source("inner.a"));
+        assert_eq!(ConfigSource::Default, config.source("list"));
+    }
+
+    #[test]
+    fn blames_the_invalid_layer_only() {
+        let e = merge(&[
+            ("(inner: (a: 1))", file("test.ron")),
+            ("(inner: (b: 2.0))", file("test.user.ron")),
+            (
+                "(inner: (c: 3))",
+                ConfigSource::Env("PONG_TEST__INNER__C".into()),
+            ),
+        ])
+        .unwrap_err();
+
+        let message = e.to_string();
+        assert!(message.contains("in $PONG_TEST__INNER__C"), "{}", message);
+    }
+
+    #[test]
+    fn env_vars_override_nested_fields() {
+        std::env::set_var("PONG_ENV_TEST__INNER__B", "4.5");
+        std::env::set_var("PONG_ENV_TEST__CHOICE", "On(6)");
+
+        let config = ConfigLoader::new("env_test").load::<Outer>().unwrap();
+
+        assert_eq!(Inner { a: 0, b: 4.5 }, config.value.inner);
+        assert_eq!(Choice::On(6), config.value.choice);
+        assert!(config
+            .report()
+            .contains("inner.b = 4.5 ($PONG_ENV_TEST__INNER__B)\n"));
+    }
+}
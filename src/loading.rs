@@ -0,0 +1,249 @@
+use crate::{
+    audio::{initialise_audio, load_impact_sounds},
+    error::PongError,
+    fatal_error::FatalError,
+    pong::Pong,
+    session::Session,
+    toast::Toasts,
+    viewport::ArenaUi,
+};
+use amethyst::{
+    assets::{AssetStorage, Completion, Handle, Loader, ProgressCounter},
+    ecs::prelude::Entity,
+    prelude::*,
+    renderer::{ImageFormat, SpriteSheet, SpriteSheetFormat, Texture},
+    ui::{Anchor, FontHandle, TtfFormat, UiImage, UiText, UiTransform},
+};
+use std::path::PathBuf;
+
+/// Width of the progress bar when every asset is loaded.
+const BAR_WIDTH: f32 = 300.;
+const BAR_HEIGHT: f32 = 12.;
+const BAR_BACKGROUND: [f32; 4] = [0.2, 0.21, 0.31, 1.0];
+const BAR_FILL: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+
+const SPRITE_SHEET_TEXTURE: &str = "texture/pong_spritesheet.png";
+const SPRITE_SHEET: &str = "texture/pong_spritesheet.ron";
+const FONT: &str = "font/square.ttf";
+/// Assets the game cannot be played without.
+const ESSENTIAL_ASSETS: [&str; 3] = [SPRITE_SHEET_TEXTURE, SPRITE_SHEET, FONT];
+
+/// Assets loaded before the game starts.
+pub struct GameAssets {
+    pub sprite_sheet: Handle<SpriteSheet>,
+    /// Font used by all UI text.
+    pub font: FontHandle,
+}
+
+/// Loads the sprite sheet, font and audio, showing progress until they are ready.
+///
+/// Gameplay starts once every asset has finished loading. The game cannot be played without the
+/// sprite sheet or the font, so their failure is fatal. Any other asset is optional, and the game
+/// starts without it, telling the player what failed.
+pub struct Loading {
+    /// Directory or URL the audio manifest is read from.
+    assets_dir: PathBuf,
+    /// Whether music and sound effects are loaded.
+    audio: bool,
+    progress: ProgressCounter,
+    /// Whether the audio manifest could not be read, in which case no music is loaded.
+    manifest_failed: bool,
+    bar_fill: Option<Entity>,
+    entities: Vec<Entity>,
+}
+
+impl Loading {
+    pub fn new(assets_dir: PathBuf, audio: bool) -> Self {
+        Loading {
+            assets_dir,
+            audio,
+            progress: ProgressCounter::new(),
+            manifest_failed: false,
+            bar_fill: None,
+            entities: Vec::new(),
+        }
+    }
+
+    /// Returns the share of assets that finished loading, successfully or not.
+    fn fraction(&self) -> f32 {
+        match self.progress.num_assets() {
+            0 => 1.0,
+            num_assets => {
+                (self.progress.num_finished() + self.progress.num_failed()) as f32
+                    / num_assets as f32
+            }
+        }
+    }
+
+    /// Returns the error of the first essential asset that failed to load, if any.
+    fn essential_failure(&self) -> Option<PongError> {
+        self.progress
+            .errors()
+            .into_iter()
+            .find(|failure| ESSENTIAL_ASSETS.contains(&failure.asset_name.as_str()))
+            .map(|failure| PongError::Asset {
+                path: failure.asset_name,
+                message: failure.error.to_string(),
+            })
+    }
+
+    /// Logs the assets that failed to load, and tells the player the game goes on without them.
+    fn report_failures(&self, world: &mut World) {
+        let mut failed = self
+            .progress
+            .errors()
+            .into_iter()
+            .map(|failure| {
+                log::error!("Failed to load `{}`: {}", failure.asset_name, failure.error);
+                failure.asset_name
+            })
+            .collect::<Vec<_>>();
+        if self.manifest_failed {
+            failed.push(crate::AUDIO_MANIFEST.to_string());
+        }
+
+        if !failed.is_empty() {
+            world
+                .write_resource::<Toasts>()
+                .push(format!("Playing without {}", failed.join(", ")));
+        }
+    }
+}
+
+impl SimpleState for Loading {
+    fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        let StateData { world, .. } = data;
+
+        // Headless runs draw nothing, so there is nothing to load, and the game starts at once.
+        if world.read_resource::<Session>().headless {
+            return;
+        }
+
+        let sprite_sheet = load_sprite_sheet(world, &mut self.progress);
+        let font = world.read_resource::<Loader>().load(
+            FONT,
+            TtfFormat,
+            &mut self.progress,
+            &world.read_resource(),
+        );
+        if self.audio {
+            self.manifest_failed =
+                initialise_audio(world, &self.assets_dir, &mut self.progress).is_err();
+        }
+        world.insert(GameAssets {
+            sprite_sheet,
+            font: font.clone(),
+        });
+
+        let background = create_bar(world, "loading_bar", BAR_WIDTH, 1., BAR_BACKGROUND);
+        let fill = create_bar(world, "loading_bar_fill", 0., 1.5, BAR_FILL);
+        self.bar_fill = Some(fill);
+
+        // The text shows up once the font itself has loaded.
+        let transform = UiTransform::new(
+            "loading".to_string(),
+            Anchor::Middle,
+            Anchor::Middle,
+            0.,
+            30.,
+            2.,
+            300.,
+            30.,
+        );
+        let text = world
+            .create_entity()
+            .with(ArenaUi::new(&transform, 20.))
+            .with(transform)
+            .with(UiText::new(
+                font,
+                "Loading".to_string(),
+                [1.0, 1.0, 1.0, 1.0],
+                20.,
+            ))
+            .build();
+
+        self.entities.extend(&[background, fill, text]);
+    }
+
+    fn on_stop(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        let StateData { world, .. } = data;
+
+        if let Err(e) = world.delete_entities(&self.entities) {
+            log::error!("Failed to delete the loading screen: {}", e);
+        }
+        self.entities.clear();
+        self.bar_fill = None;
+
+        load_impact_sounds(world);
+    }
+
+    fn update(&mut self, data: &mut StateData<'_, GameData<'_, '_>>) -> SimpleTrans {
+        // The bar is laid out again with its new width by the `ViewportSystem`.
+        if let Some(bar_fill) = self.bar_fill {
+            if let Some(arena_ui) = data.world.write_storage::<ArenaUi>().get_mut(bar_fill) {
+                arena_ui.width = BAR_WIDTH * self.fraction();
+                arena_ui.laid_out_for = None;
+            }
+        }
+
+        match self.progress.complete() {
+            Completion::Loading => Trans::None,
+            // Failures are reported once every other asset is done.
+            Completion::Failed if self.progress.num_loading() > 0 => Trans::None,
+            Completion::Complete | Completion::Failed => {
+                if let Some(error) = self.essential_failure() {
+                    return Trans::Switch(Box::new(FatalError::new(error)));
+                }
+                self.report_failures(data.world);
+                Trans::Switch(Box::new(Pong::default()))
+            }
+        }
+    }
+}
+
+/// Creates a bar of the progress bar, growing from the left.
+fn create_bar(world: &mut World, id: &str, width: f32, z: f32, colour: [f32; 4]) -> Entity {
+    let transform = UiTransform::new(
+        id.to_string(),
+        Anchor::Middle,
+        Anchor::MiddleLeft,
+        -BAR_WIDTH * 0.5,
+        0.,
+        z,
+        width,
+        BAR_HEIGHT,
+    );
+    world
+        .create_entity()
+        .with(ArenaUi::new(&transform, 0.))
+        .with(transform)
+        .with(UiImage::SolidColor(colour))
+        .build()
+}
+
+fn load_sprite_sheet(world: &mut World, progress: &mut ProgressCounter) -> Handle<SpriteSheet> {
+    // Load the sprite sheet necessary to render the graphics.
+    // The texture is the pixel data
+    // `sprite_sheet` is the layout of the sprites on the image
+    // `texture_handle` is a cloneable reference to the texture
+
+    let texture_handle = {
+        let loader = world.read_resource::<Loader>();
+        let texture_storage = world.read_resource::<AssetStorage<Texture>>();
+        loader.load(
+            SPRITE_SHEET_TEXTURE,
+            ImageFormat::default(),
+            &mut *progress,
+            &texture_storage,
+        )
+    };
+
+    let loader = world.read_resource::<Loader>();
+    let sprite_sheet_store = world.read_resource::<AssetStorage<SpriteSheet>>();
+    loader.load(
+        SPRITE_SHEET,                      // Here we load the associated ron file
+        SpriteSheetFormat(texture_handle), // We pass it the texture we want it to use
+        progress,
+        &sprite_sheet_store,
+    )
+}
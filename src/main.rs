@@ -1,15 +1,49 @@
 //! Pong
 
 mod audio;
+mod bindings;
 mod bundle;
+#[cfg(not(feature = "wasm"))]
+mod cli;
+#[cfg(feature = "embedded-assets")]
+mod embedded_assets;
+mod error;
 mod event;
+mod event_log;
+mod fatal_error;
+mod frame_time;
+mod game_config;
+mod game_over;
+// Only the wasm build is embedded in a page that sends commands and reads the state back.
+#[cfg(feature = "wasm")]
+mod host;
+mod input_profile;
+// The page gives the configs as text on wasm, where there are no files to watch.
+#[cfg_attr(feature = "wasm", allow(dead_code))]
+mod layered_config;
+mod loading;
+mod paused;
+mod pointer;
 mod pong;
+mod save;
+mod session;
+mod settings;
+mod stats;
+mod storage;
 mod systems;
+mod toast;
+mod viewport;
 
-use std::{path::Path, time::Duration};
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
+#[cfg(feature = "wasm")]
+use amethyst::{assets::Processor, audio::Source};
+#[cfg(not(feature = "wasm"))]
+use amethyst::{audio::AudioBundle, window::DisplayConfig};
 use amethyst::{
-    audio::{AudioBundle, DjSystemDesc},
     core::{frame_limiter::FrameRateLimitStrategy, transform::TransformBundle},
     ecs::{Component, DenseVecStorage},
     input::{Bindings, InputBundle, StringBindings},
@@ -24,27 +58,14 @@ use amethyst::{
     utils::application_root_dir,
     window::EventLoop,
 };
-#[cfg(not(feature = "wasm"))]
-use amethyst::{config::Config, window::DisplayConfig};
-
-use crate::{audio::Music, bundle::PongBundle};
+use serde::{Deserialize, Serialize};
 
-const ARENA_HEIGHT: f32 = 100.0;
-const ARENA_WIDTH: f32 = 100.0;
-const PADDLE_HEIGHT: f32 = 16.0;
-const PADDLE_WIDTH: f32 = 4.0;
-const PADDLE_VELOCITY: f32 = 75.0;
-
-const BALL_VELOCITY_X: f32 = 75.0;
-const BALL_VELOCITY_Y: f32 = 50.0;
-const BALL_RADIUS: f32 = 2.0;
+use crate::{
+    audio::AudioSettings, bundle::PongBundle, event_log::EventLog, game_config::GameConfig,
+    input_profile::InputProfiles, pointer::TouchInput, session::Session,
+};
 
-const AUDIO_MUSIC: &[&str] = &[
-    "audio/Computer_Music_All-Stars_-_Wheres_My_Jetpack.ogg",
-    "audio/Computer_Music_All-Stars_-_Albatross_v2.ogg",
-];
-const AUDIO_BOUNCE: &str = "audio/bounce.ogg";
-const AUDIO_SCORE: &str = "audio/score.ogg";
+const AUDIO_MANIFEST: &str = "audio/audio.ron";
 
 #[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::*;
@@ -56,26 +77,192 @@ pub fn init_panic_hook() {
 }
 
 #[cfg(not(feature = "wasm"))]
-fn main() -> amethyst::Result<()> {
+fn main() {
+    use crate::{
+        cli::{AiSides, Cli, USAGE},
+        game_config::GameMode,
+        layered_config::ConfigSource,
+        session::{Recording, HEADLESS_MAX_FRAMES},
+        stats::MatchStats,
+        systems::ConfigFiles,
+    };
+    use amethyst::window::MonitorIdent;
+
+    let cli = match Cli::from_env() {
+        Ok(cli) if cli.help => {
+            println!("{}", USAGE);
+            return;
+        }
+        Ok(cli) => cli,
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    if let Some(path) = cli.analyze.as_ref() {
+        // Goals are placed by the height of the arena the match was played in.
+        let summary = application_root_dir()
+            .map_err(amethyst::Error::from)
+            .and_then(|app_root| {
+                let config_files = ConfigFiles::new(&app_root, cli.config.clone(), None);
+                let game_config = config_files.game.load::<GameConfig>()?.value;
+                let records = crate::event_log::load(path)?;
+                let mut stats = MatchStats::default();
+                for record in &records {
+                    stats.record(&record.event, game_config.arena.height);
+                }
+                Ok(stats)
+            });
+        match summary {
+            Ok(summary) => print!("{}", summary),
+            Err(e) => {
+                eprintln!("error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    if cli.print_config {
+        let report = application_root_dir()
+            .map_err(amethyst::Error::from)
+            .and_then(|app_root| ConfigFiles::new(&app_root, cli.config, cli.bindings).report());
+        match report {
+            Ok(report) => print!("{}", report),
+            Err(e) => {
+                eprintln!("error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
     amethyst::start_logger(Default::default());
 
-    let setup_fn = |app_root: &Path, event_loop: &EventLoop<()>| {
-        let key_bindings_path = {
-            if cfg!(feature = "sdl_controller") {
-                app_root.join("config/input_controller.ron")
-            } else {
-                app_root.join("config/input.ron")
+    let setup_fn = move |app_root: &Path, event_loop: &EventLoop<()>| {
+        crate::storage::migrate_platform();
+
+        let config_files = ConfigFiles::new(app_root, cli.config, cli.bindings);
+        let bindings = config_files.input.load::<Bindings<StringBindings>>()?.value;
+        let input_profiles = config_files
+            .input_profiles
+            .load::<InputProfiles>()
+            .map(|config| config.value)
+            .unwrap_or_else(|e| {
+                log::error!("Ignoring the input profiles: {}", e);
+                InputProfiles::default()
+            });
+        let game = config_files.game.load::<GameConfig>()?;
+        game.value.validate(|path| game.source(path))?;
+        let mut game_config = game.value;
+        // The audio settings are saved by the game, so an invalid file falls back to the
+        // defaults rather than keeping the game from starting.
+        let audio_settings = match config_files.audio.load::<AudioSettings>() {
+            Ok(config) => config.value,
+            Err(e) => {
+                log::error!("Ignoring the audio settings: {}", e);
+                AudioSettings::default()
             }
         };
-        let bindings = <Bindings<StringBindings> as Config>::load(key_bindings_path)?;
 
-        let display_config = DisplayConfig::load(app_root.join("config/display.ron"))?;
-        let rendering_bundle = RenderingBundle::<DefaultBackend>::new(display_config, event_loop);
+        let mut session = Session {
+            headless: cli.headless,
+            max_frames: if cli.headless {
+                Some(cli.max_frames.unwrap_or(HEADLESS_MAX_FRAMES))
+            } else {
+                None
+            },
+            record: cli.record,
+            stats: cli.stats,
+            ..Default::default()
+        };
+        if let Some(ai) = cli.ai {
+            if ai != AiSides::Right {
+                session.left_control = PaddleControl::Ai;
+            }
+            if ai != AiSides::Left {
+                session.right_control = PaddleControl::Ai;
+            }
+        }
+        if let Some(seed) = cli.seed {
+            game_config.rules.seed = Some(seed);
+        }
+        if let Some(mode) = cli.mode {
+            game_config.rules.mode = mode;
+        }
+        // A replay ends with its recording.
+        if session.headless && cli.replay.is_none() && game_config.rules.mode == GameMode::Endless {
+            return Err(amethyst::Error::from_string(
+                "A headless run quits once the match is over, so it cannot play `Endless` mode",
+            ));
+        }
+        // The serves of a recorded match must replay the same way.
+        if session.record.is_some() && game_config.rules.seed.is_none() {
+            game_config.rules.seed = Some(rand::random());
+        }
+        if let Some(replay_path) = cli.replay {
+            let replay = Recording::load(&replay_path).map_err(|e| {
+                amethyst::Error::from_string(format!(
+                    "Failed to load the recording `{}`: {}",
+                    replay_path.display(),
+                    e
+                ))
+            })?;
+            replay
+                .config
+                .validate(|_| ConfigSource::File(replay_path.clone()))?;
+            game_config = replay.config.clone();
+            session.left_control = PaddleControl::Replay;
+            session.right_control = PaddleControl::Replay;
+            session.replay = Some(replay);
+        }
 
-        Ok((bindings, rendering_bundle))
+        let mut display_config = config_files.display.load::<DisplayConfig>()?.value;
+        if cli.width.is_some() || cli.height.is_some() {
+            let (width, height) = display_config.dimensions.unwrap_or((
+                crate::viewport::REFERENCE_SIZE as u32,
+                crate::viewport::REFERENCE_SIZE as u32,
+            ));
+            display_config.dimensions =
+                Some((cli.width.unwrap_or(width), cli.height.unwrap_or(height)));
+        }
+        if cli.fullscreen {
+            display_config.fullscreen = Some(MonitorIdent::from_primary(event_loop));
+        }
+        // Headless runs open no window, so that they run without a display or GPU.
+        let rendering_bundle = if session.headless {
+            None
+        } else {
+            Some(RenderingBundle::<DefaultBackend>::new(
+                display_config,
+                event_loop,
+            ))
+        };
+
+        let event_log = EventLog::new(&game_config.event_log).map_err(|e| {
+            amethyst::Error::from_string(format!("Failed to open the event log: {}", e))
+        })?;
+
+        Ok(AppSetup {
+            bindings,
+            input_profiles,
+            game_config,
+            rendering_bundle,
+            touch_input: TouchInput::default(),
+            assets_dir: app_root.join("assets"),
+            audio: !session.headless,
+            audio_settings,
+            session,
+            event_log,
+            config_files,
+        })
     };
 
-    run_application(setup_fn)
+    if let Err(e) = run_application(setup_fn) {
+        eprintln!("error: {}", e);
+        std::process::exit(1);
+    }
 }
 
 #[allow(unused)]
@@ -84,19 +271,32 @@ fn main() {}
 
 #[cfg(feature = "wasm")]
 mod wasm {
-    use std::path::Path;
+    use std::path::{Path, PathBuf};
 
     use amethyst::{
-        config::Config,
         input::{Axis, Bindings, Button, StringBindings},
         renderer::{types::DefaultBackend, RenderingBundle},
         window::{DisplayConfig, EventLoop},
         winit::event::VirtualKeyCode,
         LoggerConfig,
     };
-    use wasm_bindgen::prelude::*;
+    use serde::{de::DeserializeOwned, Serialize};
+    use wasm_bindgen::{prelude::*, JsCast};
     use web_sys::HtmlCanvasElement;
 
+    use crate::{
+        audio::AudioSettings,
+        event_log::EventLog,
+        game_config::{GameConfig, GameMode},
+        host::{HostLink, PongHandle},
+        input_profile::InputProfiles,
+        layered_config::{ConfigLoader, LayeredConfig},
+        pointer::TouchInput,
+        session::Session,
+        viewport::CanvasSize,
+        AppSetup,
+    };
+
     /// Pong application builder.
     #[wasm_bindgen]
     #[derive(Debug, Default)]
@@ -105,6 +305,18 @@ mod wasm {
         canvas_element: Option<HtmlCanvasElement>,
         /// Input bindings data.
         input_bindings_str: Option<String>,
+        /// Game config data.
+        game_config_str: Option<String>,
+        /// Display config data.
+        display_config_str: Option<String>,
+        /// Base URL of the assets, `assets` relative to the page if not set.
+        assets_url: Option<String>,
+        /// Whether music and sound effects are played, on if not set.
+        audio: Option<bool>,
+        /// Seed overriding the one in the game config.
+        seed: Option<u32>,
+        /// Game mode overriding the one in the game config.
+        mode: Option<GameMode>,
     }
 
     #[wasm_bindgen]
@@ -120,13 +332,54 @@ mod wasm {
             self
         }
 
-        /// Sets the canvas element for the `PongAppBuilder`.
+        /// Sets the input bindings, in the RON format of `config/input.ron`.
         pub fn with_input_bindings(mut self, input_bindings_str: String) -> Self {
             self.input_bindings_str = Some(input_bindings_str);
             self
         }
 
-        pub fn run(self) {
+        /// Sets the arena, paddle, ball and rules settings, in the RON format of
+        /// `config/game.ron`.
+        pub fn with_game_config(mut self, game_config_str: String) -> Self {
+            self.game_config_str = Some(game_config_str);
+            self
+        }
+
+        /// Sets the display settings, in the RON format of `config/display.ron`. The canvas size
+        /// is used when the config has no dimensions.
+        pub fn with_display_config(mut self, display_config_str: String) -> Self {
+            self.display_config_str = Some(display_config_str);
+            self
+        }
+
+        /// Sets the base URL the assets are fetched from.
+        pub fn with_assets_url(mut self, assets_url: String) -> Self {
+            self.assets_url = Some(assets_url);
+            self
+        }
+
+        /// Sets whether music and sound effects are played.
+        pub fn with_audio(mut self, audio: bool) -> Self {
+            self.audio = Some(audio);
+            self
+        }
+
+        /// Sets the seed of the random serves, so that a match can be replayed.
+        pub fn with_seed(mut self, seed: u32) -> Self {
+            self.seed = Some(seed);
+            self
+        }
+
+        /// Sets whether matches end at the score limit.
+        pub fn with_mode(mut self, mode: GameMode) -> Self {
+            self.mode = Some(mode);
+            self
+        }
+
+        /// Starts the game, returning a handle to control it.
+        ///
+        /// Throws an `Error` when one of the configs is invalid.
+        pub fn run(self) -> Result<PongHandle, JsValue> {
             // Make panic return a stack trace
             crate::init_panic_hook();
 
@@ -143,14 +396,30 @@ mod wasm {
                 .map(|canvas_element| (canvas_element.width(), canvas_element.height()));
             log::debug!("dimensions: {:?}", dimensions);
 
-            let display_config = DisplayConfig {
-                dimensions,
-                ..Default::default()
-            };
+            let mut display_config =
+                page_config::<DisplayConfig>("display", self.display_config_str.clone())?.value;
+            if display_config.dimensions.is_none() {
+                display_config.dimensions = dimensions;
+            }
 
-            let bindings = if let Some(input_bindings_str) = self.input_bindings_str.as_ref() {
-                <Bindings<StringBindings> as Config>::load_bytes(input_bindings_str.as_bytes())
-                    .expect("Failed to deserialize input bindings.")
+            let game = page_config::<GameConfig>("game", self.game_config_str.clone())?;
+            game.value
+                .validate(|path| game.source(path))
+                .map_err(|e| JsValue::from(js_sys::Error::new(&e.to_string())))?;
+            let mut game_config = game.value;
+            if let Some(seed) = self.seed {
+                game_config.rules.seed = Some(u64::from(seed));
+            }
+            if let Some(mode) = self.mode {
+                game_config.rules.mode = mode;
+            }
+
+            crate::storage::migrate_platform();
+            let bindings = if let Some(bindings) = crate::bindings::load_saved() {
+                log::debug!("Using bindings saved in `localStorage`.");
+                bindings
+            } else if let Some(input_bindings_str) = self.input_bindings_str.clone() {
+                page_config::<Bindings<StringBindings>>("input", Some(input_bindings_str))?.value
             } else {
                 // Hard coded bindings
                 log::debug!("Using built in bindings.");
@@ -166,80 +435,241 @@ mod wasm {
                     neg: Button::Key(VirtualKeyCode::Down),
                 };
                 let _ = bindings.insert_axis("right_paddle", right_paddle_axis);
+                let _ = bindings.insert_action_binding(
+                    "toggle_mute".to_string(),
+                    vec![Button::Key(VirtualKeyCode::M)],
+                );
+                let _ = bindings.insert_action_binding(
+                    "volume_up".to_string(),
+                    vec![Button::Key(VirtualKeyCode::Equals)],
+                );
+                let _ = bindings.insert_action_binding(
+                    "volume_down".to_string(),
+                    vec![Button::Key(VirtualKeyCode::Minus)],
+                );
 
                 bindings
             };
 
-            let setup_fn = move |_: &Path, event_loop: &EventLoop<()>| {
-                let rendering_bundle = RenderingBundle::<DefaultBackend>::new(
+            // Touches on the canvas drive the paddle on the touched half.
+            let touch_input = TouchInput::default();
+            if let Some(canvas_element) = self.canvas_element.as_ref() {
+                if let Err(e) = crate::pointer::listen_to_canvas(canvas_element, &touch_input) {
+                    log::error!("Failed to listen to canvas touch events: {:?}", e);
+                }
+            }
+
+            // The canvas fills its container, which the page sizes and resizes.
+            let canvas_size = CanvasSize::default();
+            if let Some(canvas_element) = self.canvas_element.as_ref() {
+                if let Err(e) = crate::viewport::listen_to_resize(canvas_element, &canvas_size) {
+                    log::error!("Failed to listen to page resizes: {:?}", e);
+                }
+            }
+
+            let host_link = HostLink::default();
+            let event_log = EventLog::new(&game_config.event_log);
+            let handle = PongHandle::new(host_link.clone(), event_log.clone());
+            // The game pauses itself when the tab is hidden or loses focus.
+            if let Err(e) = crate::host::listen_to_focus(&host_link) {
+                log::error!("Failed to listen to focus changes: {:?}", e);
+            }
+
+            let canvas_element = self.canvas_element;
+            let assets_url = self.assets_url;
+            let audio = self.audio.unwrap_or(true);
+            let setup_fn = move |app_root: &Path, event_loop: &EventLoop<()>| {
+                let rendering_bundle = Some(RenderingBundle::<DefaultBackend>::new(
                     display_config,
                     event_loop,
-                    self.canvas_element,
-                );
-
-                Ok((bindings, rendering_bundle))
+                    canvas_element,
+                ));
+
+                Ok(AppSetup {
+                    bindings,
+                    input_profiles: InputProfiles::default(),
+                    game_config,
+                    rendering_bundle,
+                    touch_input,
+                    host_link,
+                    canvas_size,
+                    assets_dir: assets_url
+                        .map(PathBuf::from)
+                        .unwrap_or_else(|| app_root.join("assets")),
+                    audio,
+                    audio_settings: AudioSettings::load(),
+                    session: Session::default(),
+                    event_log,
+                })
             };
 
-            let res = super::run_application(setup_fn);
-            match res {
+            // The event loop never returns on the web, so the game is started on the next tick to
+            // hand the handle back first.
+            let start = Closure::once(move || match super::run_application(setup_fn) {
                 Ok(_) => log::info!("Exited without error"),
-                Err(e) => log::error!("Main returned an error: {:?}", e),
-            }
+                Err(e) => {
+                    log::error!("Main returned an error: {:?}", e);
+                    crate::host::report_error(&e.to_string());
+                }
+            });
+            web_sys::window()
+                .ok_or_else(|| JsValue::from(js_sys::Error::new("No `window` to run in")))?
+                .set_timeout_with_callback(start.as_ref().unchecked_ref())?;
+            start.forget();
+
+            Ok(handle)
         }
     }
+
+    /// Loads a config from the RON text given by the page, over the defaults. Returns a JS
+    /// `Error` describing the config when it is invalid.
+    fn page_config<T>(name: &'static str, text: Option<String>) -> Result<LayeredConfig<T>, JsValue>
+    where
+        T: Default + Serialize + DeserializeOwned,
+    {
+        let loader = ConfigLoader::new(name);
+        let loader = match text {
+            Some(text) => loader.with_text(text),
+            None => loader,
+        };
+        loader
+            .load()
+            .map(|config| {
+                log::debug!("{} config:\n{}", name, config.report());
+                config
+            })
+            .map_err(|e| js_sys::Error::new(&e.to_string()).into())
+    }
+}
+
+/// Platform specific parts of the application, built by the setup function.
+pub struct AppSetup {
+    /// Input bindings for the paddle axes.
+    pub bindings: Bindings<StringBindings>,
+    /// Response curves of the paddle axes.
+    pub input_profiles: InputProfiles,
+    /// Arena, paddle and ball settings, and the rules of a match.
+    pub game_config: GameConfig,
+    /// Rendering bundle for the window or canvas, `None` when running headless.
+    pub rendering_bundle: Option<RenderingBundle<DefaultBackend>>,
+    /// Touch points fed by the platform.
+    pub touch_input: TouchInput,
+    /// Commands from and state for the embedding page.
+    #[cfg(feature = "wasm")]
+    pub host_link: crate::host::HostLink,
+    /// Size the canvas is resized to.
+    #[cfg(feature = "wasm")]
+    pub canvas_size: crate::viewport::CanvasSize,
+    /// Directory or URL the assets are loaded from.
+    pub assets_dir: PathBuf,
+    /// Whether music and sound effects are played.
+    pub audio: bool,
+    /// Volumes of the music and sound effects.
+    pub audio_settings: AudioSettings,
+    /// How the paddles are driven, and whether the match is recorded or replayed.
+    pub session: Session,
+    /// Where the gameplay events are logged.
+    pub event_log: EventLog,
+    /// Config files watched for changes.
+    #[cfg(not(feature = "wasm"))]
+    pub config_files: crate::systems::ConfigFiles,
 }
 
 fn run_application<FnSetupBundle>(setup_fn: FnSetupBundle) -> amethyst::Result<()>
 where
-    FnSetupBundle:
-        FnOnce(
-            &Path,
-            &EventLoop<()>,
-        )
-            -> amethyst::Result<(Bindings<StringBindings>, RenderingBundle<DefaultBackend>)>,
+    FnSetupBundle: FnOnce(&Path, &EventLoop<()>) -> amethyst::Result<AppSetup>,
 {
-    use crate::pong::Pong;
+    use crate::loading::Loading;
 
     let app_root = application_root_dir()?;
-    let assets_dir = app_root.join("assets");
 
     let event_loop = EventLoop::new();
 
-    let (bindings, rendering_bundle) = setup_fn(&app_root, &event_loop)?;
+    let AppSetup {
+        bindings,
+        input_profiles,
+        game_config,
+        rendering_bundle,
+        touch_input,
+        #[cfg(feature = "wasm")]
+        host_link,
+        #[cfg(feature = "wasm")]
+        canvas_size,
+        assets_dir,
+        audio,
+        audio_settings,
+        session,
+        event_log,
+        #[cfg(not(feature = "wasm"))]
+        config_files,
+    } = setup_fn(&app_root, &event_loop)?;
 
     let game_data = GameDataBuilder::default()
         // Add the transform bundle which handles tracking entity positions
         .with_bundle(TransformBundle::new())?
-        .with_bundle(InputBundle::<StringBindings>::new().with_bindings(bindings))?
-        .with_bundle(UiBundle::<StringBindings>::new())?
-        .with_bundle(
-            rendering_bundle
-                // The RenderToWindow plugin provides all the scaffolding for opening a window and
-                // drawing on it
-                .with_plugin(RenderToWindow::new().with_clear(ClearColor {
-                    float32: [0.34, 0.36, 0.52, 1.0],
-                }))
-                .with_plugin(RenderFlat2D::default())
-                .with_plugin(RenderUi::default()),
-        )?;
-
-    // Sound is currently not supported on wasm target
-    let game_data = game_data
-        .with_bundle(AudioBundle::default())?
-        .with_system_desc(
-            DjSystemDesc::new(|music: &mut Music| music.music.next()),
-            "dj_system",
-            &[],
-        );
-
-    let game_data = game_data.with_bundle(PongBundle)?;
-
-    let game = Application::build(assets_dir, Pong::default())?
-        .with_frame_limit(
+        .with_bundle(InputBundle::<StringBindings>::new().with_bindings(bindings))?;
+    let display = rendering_bundle.is_some();
+    let game_data = match rendering_bundle {
+        Some(rendering_bundle) => game_data
+            .with_bundle(UiBundle::<StringBindings>::new())?
+            .with_bundle(
+                rendering_bundle
+                    // The RenderToWindow plugin provides all the scaffolding for opening a window
+                    // and drawing on it
+                    .with_plugin(RenderToWindow::new().with_clear(ClearColor {
+                        float32: [0.34, 0.36, 0.52, 1.0],
+                    }))
+                    .with_plugin(RenderFlat2D::default())
+                    .with_plugin(RenderUi::default()),
+            )?,
+        None => game_data,
+    };
+
+    // On wasm, sounds are played by the `WebAudioSystem` in `PongBundle`, which only needs the
+    // audio assets to be processed.
+    #[cfg(not(feature = "wasm"))]
+    let game_data = if audio {
+        game_data.with_bundle(AudioBundle::default())?
+    } else {
+        game_data
+    };
+    #[cfg(feature = "wasm")]
+    let game_data = if audio {
+        game_data.with(Processor::<Source>::new(), "source_processor", &[])
+    } else {
+        game_data
+    };
+
+    let game_data = game_data.with_bundle(
+        PongBundle::default()
+            .with_audio(audio)
+            .with_display(display),
+    )?;
+
+    let game = Application::build(&assets_dir, Loading::new(assets_dir.clone(), audio))?;
+    // Assets are loaded from memory rather than from `assets_dir`.
+    #[cfg(feature = "embedded-assets")]
+    let game = game.with_default_source(crate::embedded_assets::EmbeddedAssets);
+    #[cfg(feature = "wasm")]
+    let game = game.with_resource(canvas_size).with_resource(host_link);
+    #[cfg(not(feature = "wasm"))]
+    let game = game.with_resource(config_files);
+    let game = game
+        .with_resource(audio_settings)
+        .with_resource(input_profiles)
+        .with_resource(game_config)
+        .with_resource(touch_input)
+        .with_resource(event_log);
+    // Headless runs step at a fixed rate, as fast as they can.
+    let game = if session.headless {
+        game.with_frame_limit(FrameRateLimitStrategy::Unlimited, 0)
+    } else {
+        game.with_frame_limit(
             FrameRateLimitStrategy::SleepAndYield(Duration::from_millis(2)),
             144,
         )
-        .build(game_data)?;
+    };
+    let game = game.with_resource(session).build(game_data)?;
 
     log::debug!("Before `run_winit_loop`.");
     game.run_winit_loop(event_loop);
@@ -254,17 +684,39 @@ impl Component for Ball {
     type Storage = DenseVecStorage<Self>;
 }
 
-#[derive(PartialEq, Eq)]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub enum Side {
     Left,
     Right,
 }
 
+/// How a paddle is driven.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PaddleControl {
+    /// The paddle moves along its input axis.
+    Axis,
+    /// The paddle follows the mouse cursor or a touch point.
+    Pointer,
+    /// The paddle follows the ball on its own.
+    Ai,
+    /// The paddle moves as in a recorded match.
+    Replay,
+}
+
+impl Default for PaddleControl {
+    fn default() -> Self {
+        PaddleControl::Axis
+    }
+}
+
 pub struct Paddle {
     pub velocity: f32,
     pub side: Side,
     pub width: f32,
     pub height: f32,
+    pub control: PaddleControl,
 }
 
 impl Paddle {
@@ -274,6 +726,7 @@ impl Paddle {
             side,
             width: 1.0,
             height: 1.0,
+            control: PaddleControl::Axis,
         }
     }
 }
@@ -286,13 +739,44 @@ impl Component for Paddle {
 pub struct ScoreBoard {
     score_left: i32,
     score_right: i32,
+    /// Points needed to win, `None` when matches never end.
+    score_limit: Option<i32>,
 }
 
 impl ScoreBoard {
-    pub fn new() -> ScoreBoard {
+    pub fn new(score_limit: Option<i32>) -> ScoreBoard {
         ScoreBoard {
             score_left: 0,
             score_right: 0,
+            score_limit,
+        }
+    }
+
+    /// Clears the scores for a new match.
+    pub fn reset(&mut self) {
+        self.score_left = 0;
+        self.score_right = 0;
+    }
+
+    /// Returns the side that reached the score limit, if any.
+    pub fn winner(&self) -> Option<Side> {
+        let score_limit = self.score_limit?;
+        if self.score_left >= score_limit {
+            Some(Side::Left)
+        } else if self.score_right >= score_limit {
+            Some(Side::Right)
+        } else {
+            None
+        }
+    }
+
+    /// Returns whether either side is one point away from winning.
+    pub fn is_match_point(&self) -> bool {
+        match self.score_limit {
+            Some(score_limit) => {
+                self.winner().is_none() && self.score_left.max(self.score_right) >= score_limit - 1
+            }
+            None => false,
         }
     }
 }
@@ -0,0 +1,111 @@
+#[cfg(feature = "wasm")]
+use crate::host::{self, HostCommand};
+use crate::{loading::GameAssets, pointer::TouchInput, viewport::ArenaUi};
+use amethyst::{
+    core::timing::Time,
+    ecs::prelude::Entity,
+    input::InputEvent,
+    prelude::*,
+    ui::{Anchor, UiText, UiTransform},
+};
+
+/// Gameplay is paused until the embedding page resumes it.
+///
+/// When the game paused itself because the window lost focus, pressing any key or button, or
+/// touching the screen, also resumes it.
+#[derive(Default)]
+pub struct Paused {
+    message: Option<Entity>,
+    /// Whether the game was paused because the window lost focus.
+    focus_lost: bool,
+}
+
+impl Paused {
+    /// Pauses the game because the window lost focus or was hidden.
+    pub fn on_focus_lost() -> Self {
+        Paused {
+            message: None,
+            focus_lost: true,
+        }
+    }
+}
+
+impl SimpleState for Paused {
+    fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        let StateData { world, .. } = data;
+
+        world.write_resource::<Time>().set_time_scale(0.0);
+
+        let font = world.read_resource::<GameAssets>().font.clone();
+        let text = if self.focus_lost {
+            "Paused - press any key to resume"
+        } else {
+            "Paused"
+        };
+        let transform = UiTransform::new(
+            "paused".to_string(),
+            Anchor::Middle,
+            Anchor::Middle,
+            0.,
+            0.,
+            2.,
+            600.,
+            40.,
+        );
+        self.message = Some(
+            world
+                .create_entity()
+                .with(ArenaUi::new(&transform, 24.))
+                .with(transform)
+                .with(UiText::new(
+                    font,
+                    text.to_string(),
+                    [1.0, 1.0, 1.0, 1.0],
+                    24.,
+                ))
+                .build(),
+        );
+    }
+
+    fn on_stop(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        let StateData { world, .. } = data;
+
+        if let Some(message) = self.message.take() {
+            if let Err(e) = world.delete_entity(message) {
+                log::error!("Failed to delete the paused message: {}", e);
+            }
+        }
+
+        world.write_resource::<Time>().set_time_scale(1.0);
+    }
+
+    fn handle_event(
+        &mut self,
+        _data: StateData<'_, GameData<'_, '_>>,
+        event: StateEvent,
+    ) -> SimpleTrans {
+        match event {
+            StateEvent::Input(InputEvent::ButtonPressed(_)) if self.focus_lost => Trans::Pop,
+            _ => Trans::None,
+        }
+    }
+
+    fn update(&mut self, data: &mut StateData<'_, GameData<'_, '_>>) -> SimpleTrans {
+        let touched = !data
+            .world
+            .read_resource::<TouchInput>()
+            .positions()
+            .is_empty();
+        // Pausing a paused game does nothing.
+        #[cfg(feature = "wasm")]
+        let resumed = host::apply_commands(data.world, &[HostCommand::Resume, HostCommand::Pause])
+            == Some(HostCommand::Resume);
+        #[cfg(not(feature = "wasm"))]
+        let resumed = false;
+        if resumed || (self.focus_lost && touched) {
+            Trans::Pop
+        } else {
+            Trans::None
+        }
+    }
+}
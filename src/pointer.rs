@@ -0,0 +1,107 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+/// Active touch points, in physical pixels relative to the top left of the window.
+///
+/// The touch points are shared behind an `Arc`, so that platform event listeners outside the
+/// `World` -- such as the canvas listeners on wasm -- can feed the same resource.
+#[derive(Clone, Debug, Default)]
+pub struct TouchInput {
+    touches: Arc<Mutex<HashMap<u64, (f32, f32)>>>,
+}
+
+impl TouchInput {
+    /// Records the position of a touch that started or moved.
+    pub fn touch_moved(&self, id: u64, position: (f32, f32)) {
+        if let Ok(mut touches) = self.touches.lock() {
+            touches.insert(id, position);
+        }
+    }
+
+    /// Forgets a touch that ended or was cancelled.
+    pub fn touch_ended(&self, id: u64) {
+        if let Ok(mut touches) = self.touches.lock() {
+            touches.remove(&id);
+        }
+    }
+
+    /// Returns the positions of all active touches.
+    pub fn positions(&self) -> Vec<(f32, f32)> {
+        self.touches
+            .lock()
+            .map(|touches| touches.values().copied().collect())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(feature = "wasm")]
+pub use self::wasm::listen_to_canvas;
+
+#[cfg(feature = "wasm")]
+mod wasm {
+    use wasm_bindgen::{prelude::*, JsCast};
+    use web_sys::{HtmlCanvasElement, TouchEvent};
+
+    use super::TouchInput;
+
+    /// Registers touch listeners on the canvas that feed `touch_input`.
+    pub fn listen_to_canvas(
+        canvas: &HtmlCanvasElement,
+        touch_input: &TouchInput,
+    ) -> Result<(), JsValue> {
+        let on_touch_move = {
+            let canvas = canvas.clone();
+            let touch_input = touch_input.clone();
+            Closure::wrap(Box::new(move |event: TouchEvent| {
+                // Stop the page from scrolling while a paddle is being dragged.
+                event.prevent_default();
+
+                // Touch coordinates are in CSS pixels, whereas the canvas is rendered in device
+                // pixels.
+                let rect = canvas.get_bounding_client_rect();
+                let scale_x = f64::from(canvas.width()) / rect.width();
+                let scale_y = f64::from(canvas.height()) / rect.height();
+
+                let touches = event.changed_touches();
+                (0..touches.length())
+                    .filter_map(|index| touches.get(index))
+                    .for_each(|touch| {
+                        let x = (f64::from(touch.client_x()) - rect.left()) * scale_x;
+                        let y = (f64::from(touch.client_y()) - rect.top()) * scale_y;
+                        touch_input.touch_moved(touch.identifier() as u64, (x as f32, y as f32));
+                    });
+            }) as Box<dyn FnMut(TouchEvent)>)
+        };
+
+        let on_touch_end = {
+            let touch_input = touch_input.clone();
+            Closure::wrap(Box::new(move |event: TouchEvent| {
+                let touches = event.changed_touches();
+                (0..touches.length())
+                    .filter_map(|index| touches.get(index))
+                    .for_each(|touch| touch_input.touch_ended(touch.identifier() as u64));
+            }) as Box<dyn FnMut(TouchEvent)>)
+        };
+
+        for event_name in &["touchstart", "touchmove"] {
+            canvas.add_event_listener_with_callback(
+                event_name,
+                on_touch_move.as_ref().unchecked_ref(),
+            )?;
+        }
+        for event_name in &["touchend", "touchcancel"] {
+            canvas.add_event_listener_with_callback(
+                event_name,
+                on_touch_end.as_ref().unchecked_ref(),
+            )?;
+        }
+
+        // The listeners live as long as the page.
+        on_touch_move.forget();
+        on_touch_end.forget();
+
+        Ok(())
+    }
+}
@@ -1,123 +1,383 @@
-use crate::{systems::ScoreText, Ball, Paddle, Side, ARENA_HEIGHT, ARENA_WIDTH};
+#[cfg(feature = "wasm")]
+use crate::host::{self, HostCommand};
+use crate::{
+    error::PongError,
+    event::PongEvent,
+    fatal_error::FatalError,
+    frame_time::FrameTime,
+    game_config::{GameConfig, GameRng},
+    game_over::GameOver,
+    loading::GameAssets,
+    paused::Paused,
+    save::{HighScores, SaveGame},
+    session::{Recording, Session},
+    settings::Settings,
+    stats::MatchStats,
+    systems::{ScoreText, ToastText},
+    viewport::{initialise_letterbox, ArenaUi},
+    Ball, Paddle, PaddleControl, ScoreBoard, Side,
+};
 use amethyst::{
-    assets::{AssetStorage, Handle, Loader},
+    assets::Handle,
     core::{timing::Time, transform::Transform},
-    ecs::prelude::World,
+    ecs::prelude::{Join, World},
+    input::{Button, InputEvent, VirtualKeyCode},
     prelude::*,
-    renderer::{Camera, ImageFormat, SpriteRender, SpriteSheet, SpriteSheetFormat, Texture},
-    ui::{Anchor, TtfFormat, UiText, UiTransform},
+    renderer::{Camera, SpriteRender, SpriteSheet},
+    shrev::EventChannel,
+    ui::{Anchor, FontHandle, UiText, UiTransform},
+    winit::event::{Event, WindowEvent},
 };
+use rand::Rng;
 
 #[derive(Default)]
 pub struct Pong {
     ball_spawn_timer: Option<f32>,
     sprite_sheet_handle: Option<Handle<SpriteSheet>>,
+    /// Scores last saved, to save them again when they change.
+    saved: Option<SaveGame>,
+    /// Next frame of the recording being replayed.
+    replay_frame: usize,
+    /// Error that keeps the match from going on, shown by the next update.
+    fatal: Option<PongError>,
 }
 
 impl SimpleState for Pong {
     fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
         let StateData { world, .. } = data;
-        use crate::audio::initialise_audio;
 
-        // Wait one second before spawning the ball.
-        self.ball_spawn_timer.replace(1.0);
+        if let Err(e) = self.start(world) {
+            self.fatal = Some(e);
+        }
+    }
 
-        // Load the spritesheet necessary to render the graphics.
-        // `spritesheet` is the layout of the sprites on the image;
-        // `texture` is the pixel data.
-        self.sprite_sheet_handle.replace(load_sprite_sheet(world));
-        initialise_paddles(world, self.sprite_sheet_handle.clone().unwrap());
-        initialise_camera(world);
-        initialise_audio(world);
-        initialise_score(world);
+    fn handle_event(
+        &mut self,
+        _data: StateData<'_, GameData<'_, '_>>,
+        event: StateEvent,
+    ) -> SimpleTrans {
+        match event {
+            StateEvent::Input(InputEvent::ButtonPressed(Button::Key(VirtualKeyCode::Escape))) => {
+                Trans::Push(Box::new(Settings::default()))
+            }
+            StateEvent::Window(Event::WindowEvent {
+                event: WindowEvent::Focused(false),
+                ..
+            }) => Trans::Push(Box::new(Paused::on_focus_lost())),
+            _ => Trans::None,
+        }
     }
 
     fn update(&mut self, data: &mut StateData<'_, GameData<'_, '_>>) -> SimpleTrans {
+        if let Some(e) = self.fatal.take() {
+            return Trans::Switch(Box::new(FatalError::new(e)));
+        }
+        if !self.step_session(data.world) {
+            print_result(data.world);
+            return Trans::Quit;
+        }
+
         if let Some(mut timer) = self.ball_spawn_timer.take() {
             // If the timer isn't expired yet, substract the time that passed since last update.
             {
-                let time = data.world.fetch::<Time>();
-                timer -= time.delta_seconds();
+                let frame_time = data.world.fetch::<FrameTime>();
+                timer -= frame_time.delta_seconds();
             }
             if timer <= 0.0 {
                 // When timer expire, spawn the ball
-                initialise_ball(data.world, self.sprite_sheet_handle.clone().unwrap());
+                let serve = initialise_ball(data.world, self.sprite_sheet_handle.clone());
+                data.world
+                    .write_resource::<EventChannel<PongEvent>>()
+                    .single_write(serve);
             } else {
                 // If timer is not expired yet, put it back onto the state.
                 self.ball_spawn_timer.replace(timer);
             }
         }
-        Trans::None
+
+        // Resuming a game in play does nothing.
+        #[cfg(feature = "wasm")]
+        match host::apply_commands(
+            data.world,
+            &[
+                HostCommand::Pause,
+                HostCommand::Resume,
+                HostCommand::FocusLost,
+            ],
+        ) {
+            Some(HostCommand::Pause) => return Trans::Push(Box::new(Paused::default())),
+            Some(HostCommand::FocusLost) => {
+                return Trans::Push(Box::new(Paused::on_focus_lost()));
+            }
+            _ => {}
+        }
+
+        if data.world.read_resource::<Session>().saves_progress() {
+            self.save_progress(data.world);
+        }
+
+        let winner = data.world.read_resource::<ScoreBoard>().winner();
+        // The stats are saved before a new match resets them.
+        if winner.is_some() {
+            save_stats(data.world);
+        }
+        // Nobody is there to quit a match that goes on for too long.
+        let out_of_frames = data
+            .world
+            .read_resource::<Session>()
+            .max_frames
+            .map_or(false, |max_frames| {
+                data.world.read_resource::<Time>().frame_number() >= max_frames
+            });
+        match winner {
+            Some(_) if data.world.read_resource::<Session>().headless => {
+                print_result(data.world);
+                Trans::Quit
+            }
+            Some(winner) => Trans::Push(Box::new(GameOver::new(winner))),
+            None if out_of_frames => {
+                print_result(data.world);
+                Trans::Quit
+            }
+            None => Trans::None,
+        }
+    }
+
+    fn on_stop(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        // A match with no rallies yet would overwrite the stats of the match won before it.
+        if data.world.read_resource::<MatchStats>().rallies > 0 {
+            save_stats(data.world);
+        }
+        let path = data.world.read_resource::<Session>().record.clone();
+        if let (Some(path), Some(recording)) = (path, data.world.remove::<Recording>()) {
+            match recording.save(&path) {
+                Ok(()) => log::info!("Recorded the match to {}", path.display()),
+                Err(e) => log::error!("Failed to save the recording: {}", e),
+            }
+        }
     }
 }
 
-fn load_sprite_sheet(world: &mut World) -> Handle<SpriteSheet> {
-    // Load the sprite sheet necessary to render the graphics.
-    // The texture is the pixel data
-    // `sprite_sheet` is the layout of the sprites on the image
-    // `texture_handle` is a cloneable reference to the texture
-
-    let texture_handle = {
-        let loader = world.read_resource::<Loader>();
-        let texture_storage = world.read_resource::<AssetStorage<Texture>>();
-        loader.load(
-            "texture/pong_spritesheet.png",
-            ImageFormat::default(),
-            (),
-            &texture_storage,
-        )
+impl Pong {
+    /// Sets up the match, failing when a resource it relies on is missing.
+    fn start(&mut self, world: &mut World) -> Result<(), PongError> {
+        // Wait one second before spawning the ball.
+        self.ball_spawn_timer.replace(1.0);
+
+        let (score_limit, seed) = {
+            let game_config = world.read_resource::<GameConfig>();
+            (game_config.score_limit(), game_config.rules.seed)
+        };
+        let (saves_progress, records) = {
+            let session = world.read_resource::<Session>();
+            (session.saves_progress(), session.record.is_some())
+        };
+        let mut score_board = ScoreBoard::new(score_limit);
+        // Carry on with the match in progress when the game was left, unless the rules changed
+        // such that it is over.
+        if let Some(save_game) = SaveGame::load().filter(|_| saves_progress) {
+            save_game.restore(&mut score_board);
+            if score_board.winner().is_some() {
+                score_board.reset();
+            }
+        }
+        if records {
+            let recording = Recording::new(world.read_resource::<GameConfig>().clone());
+            world.insert(recording);
+        }
+        world.insert(score_board);
+        world.insert(MatchStats::default());
+        world.insert(HighScores::load());
+        world.insert(GameRng::new(seed));
+
+        // Headless runs have no window, so nothing is drawn.
+        if world.read_resource::<Session>().headless {
+            initialise_paddles(world, None);
+            return Ok(());
+        }
+
+        // The sprite sheet and font were loaded by the `Loading` state.
+        let (sprite_sheet_handle, font) = {
+            let game_assets = world
+                .try_fetch::<GameAssets>()
+                .ok_or(PongError::MissingResource("GameAssets"))?;
+            (game_assets.sprite_sheet.clone(), game_assets.font.clone())
+        };
+        self.sprite_sheet_handle
+            .replace(sprite_sheet_handle.clone());
+        initialise_paddles(world, Some(sprite_sheet_handle));
+        initialise_camera(world);
+        initialise_letterbox(world);
+        initialise_score(world, font.clone());
+        initialise_toast(world, font);
+        Ok(())
+    }
+
+    /// Steps the frame as the session dictates: a replay sets the length of the frame and moves
+    /// the paddles as recorded. Returns `false` once the replay is over.
+    fn step_session(&mut self, world: &mut World) -> bool {
+        let session = world.read_resource::<Session>();
+        let replay = match session.replay.as_ref() {
+            Some(replay) => replay,
+            None => return true,
+        };
+        // Frames are only recorded while the game runs, so the replay waits out pauses.
+        if world.read_resource::<Time>().time_scale() == 0.0 {
+            return true;
+        }
+        let frame = match replay.frames.get(self.replay_frame) {
+            Some(frame) => frame,
+            None => return false,
+        };
+        self.replay_frame += 1;
+        world.write_resource::<FrameTime>().set_next(frame.delta);
+
+        let paddles = world.read_storage::<Paddle>();
+        let mut transforms = world.write_storage::<Transform>();
+        for (paddle, transform) in (&paddles, &mut transforms).join() {
+            if paddle.control == PaddleControl::Replay {
+                transform.set_translation_y(match paddle.side {
+                    Side::Left => frame.left,
+                    Side::Right => frame.right,
+                });
+            }
+        }
+        true
+    }
+
+    /// Saves the match and the high scores when the scores change. The match is forgotten once
+    /// a side has won.
+    fn save_progress(&mut self, world: &mut World) {
+        let score_board = world.read_resource::<ScoreBoard>();
+        let save_game = SaveGame::of(&score_board);
+        if self.saved == Some(save_game) {
+            return;
+        }
+        self.saved = Some(save_game);
+
+        let mut high_scores = world.write_resource::<HighScores>();
+        if high_scores.record(&score_board) {
+            high_scores.save();
+        }
+        if score_board.winner().is_some() {
+            SaveGame::clear();
+        } else {
+            save_game.save();
+        }
+    }
+}
+
+/// Saves the stats of the match to the file given by the session, if any.
+fn save_stats(world: &World) {
+    let path = match world.read_resource::<Session>().stats.clone() {
+        Some(path) => path,
+        None => return,
     };
+    let saved = world
+        .read_resource::<MatchStats>()
+        .to_json()
+        .map_err(std::io::Error::from)
+        .and_then(|json| std::fs::write(&path, json));
+    if let Err(e) = saved {
+        log::error!("Failed to save the stats to {}: {}", path.display(), e);
+    }
+}
 
-    let loader = world.read_resource::<Loader>();
-    let sprite_sheet_store = world.read_resource::<AssetStorage<SpriteSheet>>();
-    loader.load(
-        "texture/pong_spritesheet.ron", // Here we load the associated ron file
-        SpriteSheetFormat(texture_handle), // We pass it the texture we want it to use
-        (),
-        &sprite_sheet_store,
-    )
+/// Prints the scores, and the winner if any, when a headless run or a replay ends.
+fn print_result(world: &World) {
+    let score_board = world.read_resource::<ScoreBoard>();
+    match score_board.winner() {
+        Some(winner) => println!(
+            "{:?} wins {} - {}",
+            winner, score_board.score_left, score_board.score_right
+        ),
+        None => println!(
+            "No winner, {} - {}",
+            score_board.score_left, score_board.score_right
+        ),
+    }
+}
+
+/// Resets the scores and serves the ball from the centre for a new match.
+pub fn new_match(world: &mut World) {
+    world.write_resource::<ScoreBoard>().reset();
+    *world.write_resource::<MatchStats>() = MatchStats::default();
+
+    let (arena_width, arena_height) = {
+        let arena = &world.read_resource::<GameConfig>().arena;
+        (arena.width, arena.height)
+    };
+    let frame = world.read_resource::<Time>().frame_number();
+    let entities = world.entities();
+    let balls = world.read_storage::<Ball>();
+    let mut transforms = world.write_storage::<Transform>();
+    let mut pong_events = world.write_resource::<EventChannel<PongEvent>>();
+    for (entity, ball, transform) in (&entities, &balls, &mut transforms).join() {
+        transform.set_translation_xyz(arena_width / 2.0, arena_height / 2.0, 0.0);
+        pong_events.single_write(PongEvent::Serve {
+            ball: Some(entity),
+            position: [arena_width / 2.0, arena_height / 2.0],
+            velocity: ball.velocity,
+            frame,
+        });
+    }
 }
 
 /// Initialise the camera.
 fn initialise_camera(world: &mut World) {
+    let (arena_width, arena_height) = {
+        let arena = &world.read_resource::<GameConfig>().arena;
+        (arena.width, arena.height)
+    };
+
     // Setup camera in a way that our screen covers whole arena and (0, 0) is in the bottom left.
+    // The `ViewportSystem` widens it to fit the window around the arena.
     let mut transform = Transform::default();
-    transform.set_translation_xyz(ARENA_WIDTH * 0.5, ARENA_HEIGHT * 0.5, 1.0);
+    transform.set_translation_xyz(arena_width * 0.5, arena_height * 0.5, 1.0);
 
     world
         .create_entity()
-        .with(Camera::standard_2d(ARENA_WIDTH, ARENA_HEIGHT))
+        .with(Camera::standard_2d(arena_width, arena_height))
         .with(transform)
         .build();
 }
 
-/// Initialises one paddle on the left, and one paddle on the right.
-fn initialise_paddles(world: &mut World, sprite_sheet_handle: Handle<SpriteSheet>) {
-    use crate::{PADDLE_HEIGHT, PADDLE_VELOCITY, PADDLE_WIDTH};
+/// Initialises one paddle on the left, and one paddle on the right, drawn with the sprite sheet
+/// unless headless.
+fn initialise_paddles(world: &mut World, sprite_sheet_handle: Option<Handle<SpriteSheet>>) {
+    let (arena, paddle) = {
+        let game_config = world.read_resource::<GameConfig>();
+        (game_config.arena.clone(), game_config.paddle.clone())
+    };
+    let (left_control, right_control) = {
+        let session = world.read_resource::<Session>();
+        (session.control(Side::Left), session.control(Side::Right))
+    };
 
     let mut left_transform = Transform::default();
     let mut right_transform = Transform::default();
 
     // Correctly position the paddles.
-    let y = ARENA_HEIGHT / 2.0;
-    left_transform.set_translation_xyz(PADDLE_WIDTH * 0.5, y, 0.0);
-    right_transform.set_translation_xyz(ARENA_WIDTH - PADDLE_WIDTH * 0.5, y, 0.0);
+    let y = arena.height / 2.0;
+    left_transform.set_translation_xyz(paddle.width * 0.5, y, 0.0);
+    right_transform.set_translation_xyz(arena.width - paddle.width * 0.5, y, 0.0);
 
     // Assign the sprites for the paddles
-    let sprite_render = SpriteRender {
-        sprite_sheet: sprite_sheet_handle,
+    let sprite_render = sprite_sheet_handle.map(|sprite_sheet| SpriteRender {
+        sprite_sheet,
         sprite_number: 0, // paddle is the first sprite in the sprite_sheet
-    };
+    });
 
     // Create a left plank entity.
     world
         .create_entity()
-        .with(sprite_render.clone())
+        .maybe_with(sprite_render.clone())
         .with(Paddle {
-            velocity: PADDLE_VELOCITY,
+            velocity: paddle.velocity,
             side: Side::Left,
-            width: PADDLE_WIDTH,
-            height: PADDLE_HEIGHT,
+            width: paddle.width,
+            height: paddle.height,
+            control: left_control,
         })
         .with(left_transform)
         .build();
@@ -125,90 +385,156 @@ fn initialise_paddles(world: &mut World, sprite_sheet_handle: Handle<SpriteSheet
     // Create right plank entity.
     world
         .create_entity()
-        .with(sprite_render)
+        .maybe_with(sprite_render)
         .with(Paddle {
-            velocity: PADDLE_VELOCITY,
+            velocity: paddle.velocity,
             side: Side::Right,
-            width: PADDLE_WIDTH,
-            height: PADDLE_HEIGHT,
+            width: paddle.width,
+            height: paddle.height,
+            control: right_control,
         })
         .with(right_transform)
         .build();
 }
 
-/// Initialises one ball in the middle-ish of the arena.
-fn initialise_ball(world: &mut World, sprite_sheet_handle: Handle<SpriteSheet>) {
-    use crate::{BALL_RADIUS, BALL_VELOCITY_X, BALL_VELOCITY_Y};
+/// Initialises one ball in the middle of the arena, and returns its serve.
+fn initialise_ball(
+    world: &mut World,
+    sprite_sheet_handle: Option<Handle<SpriteSheet>>,
+) -> PongEvent {
+    let (arena, ball) = {
+        let game_config = world.read_resource::<GameConfig>();
+        (game_config.arena.clone(), game_config.ball.clone())
+    };
+
+    let mut velocity = ball.velocity;
+    if ball.random_serve && world.write_resource::<GameRng>().0.gen() {
+        velocity[1] = -velocity[1];
+    }
 
     // Create the translation.
     let mut local_transform = Transform::default();
-    local_transform.set_translation_xyz(ARENA_WIDTH / 2.0, ARENA_HEIGHT / 2.0, 0.0);
+    local_transform.set_translation_xyz(arena.width / 2.0, arena.height / 2.0, 0.0);
 
     // Assign the sprite for the ball
-    let sprite_render = SpriteRender {
-        sprite_sheet: sprite_sheet_handle,
+    let sprite_render = sprite_sheet_handle.map(|sprite_sheet| SpriteRender {
+        sprite_sheet,
         sprite_number: 1, // ball is the second sprite on the sprite_sheet
-    };
+    });
 
-    world
+    let entity = world
         .create_entity()
-        .with(sprite_render)
+        .maybe_with(sprite_render)
         .with(Ball {
-            radius: BALL_RADIUS,
-            velocity: [BALL_VELOCITY_X, BALL_VELOCITY_Y],
+            radius: ball.radius,
+            velocity,
         })
         .with(local_transform)
         .build();
+
+    PongEvent::Serve {
+        ball: Some(entity),
+        position: [arena.width / 2.0, arena.height / 2.0],
+        velocity,
+        frame: world.read_resource::<Time>().frame_number(),
+    }
 }
 
-fn initialise_score(world: &mut World) {
-    let font = world.read_resource::<Loader>().load(
-        "font/square.ttf",
-        TtfFormat,
-        (),
-        &world.read_resource(),
-    );
-    let p1_transform = UiTransform::new(
-        "P1".to_string(),
-        Anchor::TopMiddle,
-        Anchor::Middle,
-        -50.,
-        -50.,
-        1.,
-        200.,
-        50.,
-    );
+/// Initialises the scores at the top of the arena, with the names of the players under them
+/// when set. They are kept up to date by the `ScoreUiSystem`.
+fn initialise_score(world: &mut World, font: FontHandle) {
+    let config = world.read_resource::<GameConfig>().score_ui.clone();
+    let sides = {
+        let score_board = world.read_resource::<ScoreBoard>();
+        [
+            (
+                "P1",
+                -config.spacing,
+                score_board.score_left,
+                &config.left_name,
+            ),
+            (
+                "P2",
+                config.spacing,
+                score_board.score_right,
+                &config.right_name,
+            ),
+        ]
+    };
+
+    let mut score_entities = Vec::with_capacity(2);
+    for (id, x, score, name) in sides.iter() {
+        let transform = UiTransform::new(
+            id.to_string(),
+            Anchor::TopMiddle,
+            Anchor::Middle,
+            *x,
+            -config.top,
+            1.,
+            200.,
+            config.font_size,
+        );
+        let entity = world
+            .create_entity()
+            .with(ArenaUi::new(&transform, config.font_size))
+            .with(transform)
+            .with(UiText::new(
+                font.clone(),
+                score.to_string(),
+                [1.0, 1.0, 1.0, 1.0],
+                config.font_size,
+            ))
+            .build();
+        score_entities.push(entity);
+
+        if !name.is_empty() {
+            let transform = UiTransform::new(
+                format!("{}_name", id),
+                Anchor::TopMiddle,
+                Anchor::Middle,
+                *x,
+                -config.top - config.font_size * 0.5 - 10.,
+                1.,
+                200.,
+                20.,
+            );
+            world
+                .create_entity()
+                .with(ArenaUi::new(&transform, 16.))
+                .with(transform)
+                .with(UiText::new(
+                    font.clone(),
+                    name.to_string(),
+                    [1.0, 1.0, 1.0, 1.0],
+                    16.,
+                ))
+                .build();
+        }
+    }
+    world.insert(ScoreText {
+        p1_score: score_entities[0],
+        p2_score: score_entities[1],
+    });
+}
 
-    let p2_transform = UiTransform::new(
-        "P2".to_string(),
-        Anchor::TopMiddle,
+/// Initialises the text at the bottom of the screen that shows toasts.
+fn initialise_toast(world: &mut World, font: FontHandle) {
+    let transform = UiTransform::new(
+        "toast".to_string(),
+        Anchor::BottomMiddle,
         Anchor::Middle,
-        50.,
-        -50.,
+        0.,
+        30.,
         1.,
-        200.,
-        50.,
+        500.,
+        30.,
     );
 
-    let p1_score = world
+    let entity = world
         .create_entity()
-        .with(p1_transform)
-        .with(UiText::new(
-            font.clone(),
-            "0".to_string(),
-            [1.0, 1.0, 1.0, 1.0],
-            50.,
-        ))
-        .build();
-    let p2_score = world
-        .create_entity()
-        .with(p2_transform)
-        .with(UiText::new(
-            font,
-            "0".to_string(),
-            [1.0, 1.0, 1.0, 1.0],
-            50.,
-        ))
+        .with(ArenaUi::new(&transform, 16.))
+        .with(transform)
+        .with(UiText::new(font, String::new(), [1.0, 1.0, 1.0, 1.0], 16.))
         .build();
-    world.insert(ScoreText { p1_score, p2_score });
+    world.insert(ToastText { entity });
 }
@@ -0,0 +1,98 @@
+//! Progress kept across launches: the match in progress and the high scores.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{ScoreBoard, Side};
+
+/// Location of the match in progress, relative to the application root.
+pub const SAVE_GAME_PATH: &str = "save/match.ron";
+/// Location of the high scores, relative to the application root.
+pub const HIGH_SCORES_PATH: &str = "save/high_scores.ron";
+
+/// Scores of a match in progress, restored on the next launch.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct SaveGame {
+    pub score_left: i32,
+    pub score_right: i32,
+}
+
+impl SaveGame {
+    pub fn of(score_board: &ScoreBoard) -> Self {
+        SaveGame {
+            score_left: score_board.score_left,
+            score_right: score_board.score_right,
+        }
+    }
+
+    /// Sets the scores of `score_board` to the saved ones.
+    pub fn restore(&self, score_board: &mut ScoreBoard) {
+        score_board.score_left = self.score_left;
+        score_board.score_right = self.score_right;
+    }
+
+    /// Loads the match in progress, if any.
+    pub fn load() -> Option<Self> {
+        crate::storage::load(SAVE_GAME_PATH)
+            .map_err(|e| log::error!("Ignoring the saved match: {}", e))
+            .ok()
+            .flatten()
+    }
+
+    pub fn save(&self) {
+        if let Err(e) = crate::storage::save(SAVE_GAME_PATH, self) {
+            log::error!("Failed to save the match: {}", e);
+        }
+    }
+
+    /// Forgets the match in progress, once it is over.
+    pub fn clear() {
+        if let Err(e) = crate::storage::remove(SAVE_GAME_PATH) {
+            log::error!("Failed to clear the saved match: {}", e);
+        }
+    }
+}
+
+/// Matches won by each side, and the best score reached.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct HighScores {
+    pub wins_left: u32,
+    pub wins_right: u32,
+    /// Most points scored by one side in a match.
+    pub best_score: i32,
+}
+
+impl HighScores {
+    /// Loads the saved high scores, starting afresh when there are none.
+    pub fn load() -> Self {
+        match crate::storage::load(HIGH_SCORES_PATH) {
+            Ok(high_scores) => high_scores.unwrap_or_default(),
+            Err(e) => {
+                log::error!("Ignoring saved high scores: {}", e);
+                HighScores::default()
+            }
+        }
+    }
+
+    pub fn save(&self) {
+        if let Err(e) = crate::storage::save(HIGH_SCORES_PATH, self) {
+            log::error!("Failed to save high scores: {}", e);
+        }
+    }
+
+    /// Records the scores and winner of a match, returning whether the high scores changed.
+    pub fn record(&mut self, score_board: &ScoreBoard) -> bool {
+        let mut changed = false;
+        let score = score_board.score_left.max(score_board.score_right);
+        if score > self.best_score {
+            self.best_score = score;
+            changed = true;
+        }
+        match score_board.winner() {
+            Some(Side::Left) => self.wins_left += 1,
+            Some(Side::Right) => self.wins_right += 1,
+            None => return changed,
+        }
+        true
+    }
+}
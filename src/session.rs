@@ -0,0 +1,80 @@
+//! How the game was launched, set from the command line natively.
+
+use amethyst::Error;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::{game_config::GameConfig, PaddleControl, Side};
+
+/// Frames a headless run quits after by default, an hour at 60 frames a second.
+pub const HEADLESS_MAX_FRAMES: u64 = 216_000;
+
+/// Options of this run of the game.
+#[derive(Default)]
+pub struct Session {
+    pub left_control: PaddleControl,
+    pub right_control: PaddleControl,
+    /// Whether the game runs without a window or audio. The simulation steps at a fixed rate, and
+    /// the game quits once the match or the replay is over.
+    pub headless: bool,
+    /// Frames the game quits after, when nobody is there to quit it.
+    pub max_frames: Option<u64>,
+    /// File the match is recorded to.
+    pub record: Option<PathBuf>,
+    /// Recorded match played back in place of the players.
+    pub replay: Option<Recording>,
+    /// File the stats of the match are written to as JSON, when it ends and when the game quits.
+    pub stats: Option<PathBuf>,
+}
+
+impl Session {
+    /// Returns how the paddle on `side` starts out being driven.
+    pub fn control(&self, side: Side) -> PaddleControl {
+        match side {
+            Side::Left => self.left_control,
+            Side::Right => self.right_control,
+        }
+    }
+
+    /// Returns whether the match and high scores are saved. Matches that are recorded, replayed
+    /// or run headless start afresh and leave the saved ones alone.
+    pub fn saves_progress(&self) -> bool {
+        !self.headless && self.record.is_none() && self.replay.is_none()
+    }
+}
+
+/// A match recorded frame by frame, which replays the same way given the same config.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct Recording {
+    /// Config the match was played with, including the seed of the serves.
+    pub config: GameConfig,
+    pub frames: Vec<RecordedFrame>,
+}
+
+/// Length of a frame, and where the paddles were at its end.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct RecordedFrame {
+    pub delta: f32,
+    pub left: f32,
+    pub right: f32,
+}
+
+impl Recording {
+    pub fn new(config: GameConfig) -> Self {
+        Recording {
+            config,
+            frames: Vec::new(),
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let serialized = std::fs::read_to_string(path)?;
+        Ok(ron::de::from_str(&serialized)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        let serialized = ron::ser::to_string(self)?;
+        std::fs::write(path, serialized)?;
+        Ok(())
+    }
+}
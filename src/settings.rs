@@ -0,0 +1,462 @@
+#[cfg(feature = "wasm")]
+use crate::host::{HostCommand, HostLink};
+use crate::{
+    audio::{step_volume, AudioSettings, VOLUME_STEP},
+    bindings::{button_name, emulated_buttons, with_emulated_buttons},
+    loading::GameAssets,
+    systems::ControllerAssignments,
+    toast::Toasts,
+    viewport::ArenaUi,
+};
+use amethyst::{
+    core::timing::Time,
+    ecs::prelude::{Entity, World},
+    input::{Bindings, Button, InputEvent, InputHandler, StringBindings, VirtualKeyCode},
+    prelude::*,
+    ui::{Anchor, FontHandle, UiText, UiTransform},
+};
+
+const COLOUR_NORMAL: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+const COLOUR_SELECTED: [f32; 4] = [1.0, 0.8, 0.2, 1.0];
+
+/// Binding that can be changed from the settings screen.
+#[derive(Clone, Debug, PartialEq)]
+enum BindingTarget {
+    /// Button that moves an axis in the positive direction.
+    AxisPos(String),
+    /// Button that moves an axis in the negative direction.
+    AxisNeg(String),
+    /// First button combination of an action.
+    Action(String),
+}
+
+/// Volume that can be changed from the settings screen.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum VolumeChannel {
+    Master,
+    Music,
+    Effects,
+}
+
+/// Setting shown on one line of the settings screen.
+#[derive(Clone, Debug, PartialEq)]
+enum SettingKind {
+    Binding(BindingTarget),
+    Volume(VolumeChannel),
+}
+
+struct SettingsEntry {
+    kind: SettingKind,
+    entity: Entity,
+}
+
+/// Settings screen that lists the paddle axes, actions and audio volumes.
+///
+/// Gameplay is paused while the screen is open. Changes apply straight away, and are saved when
+/// the screen is closed.
+#[derive(Default)]
+pub struct Settings {
+    entries: Vec<SettingsEntry>,
+    selected: usize,
+    /// Whether the next button press is captured for the selected binding.
+    capturing: bool,
+    title: Option<Entity>,
+    status: Option<Entity>,
+}
+
+impl SimpleState for Settings {
+    fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        let StateData { world, .. } = data;
+
+        world.write_resource::<Time>().set_time_scale(0.0);
+
+        let font = world.read_resource::<GameAssets>().font.clone();
+        self.title = Some(create_text(
+            world,
+            font.clone(),
+            "settings_title",
+            -40.,
+            "Settings - Enter: rebind or mute, Left/Right: volume, Esc: back",
+        ));
+
+        let kinds = {
+            let input = world.read_resource::<InputHandler<StringBindings>>();
+            binding_targets(&input.bindings)
+                .into_iter()
+                .map(SettingKind::Binding)
+                .chain(
+                    [
+                        VolumeChannel::Master,
+                        VolumeChannel::Music,
+                        VolumeChannel::Effects,
+                    ]
+                    .iter()
+                    .map(|&channel| SettingKind::Volume(channel)),
+                )
+                .collect::<Vec<_>>()
+        };
+        self.entries = kinds
+            .into_iter()
+            .enumerate()
+            .map(|(index, kind)| {
+                let entity = create_text(
+                    world,
+                    font.clone(),
+                    &format!("settings_entry_{}", index),
+                    -90. - 30. * index as f32,
+                    "",
+                );
+                SettingsEntry { kind, entity }
+            })
+            .collect();
+
+        let status_y = -110. - 30. * self.entries.len() as f32;
+        self.status = Some(create_text(world, font, "settings_status", status_y, ""));
+
+        self.refresh(world);
+    }
+
+    fn on_stop(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        let StateData { world, .. } = data;
+
+        let entities = self
+            .entries
+            .drain(..)
+            .map(|entry| entry.entity)
+            .chain(self.title.take())
+            .chain(self.status.take())
+            .collect::<Vec<_>>();
+        if let Err(e) = world.delete_entities(&entities) {
+            log::error!("Failed to delete settings entities: {}", e);
+        }
+
+        world.write_resource::<Time>().set_time_scale(1.0);
+
+        // The game was not in play when the page lost focus, so it is not paused for it now.
+        #[cfg(feature = "wasm")]
+        {
+            if let Some(host_link) = world.try_fetch::<HostLink>() {
+                host_link.discard(HostCommand::FocusLost);
+            }
+        }
+    }
+
+    fn handle_event(
+        &mut self,
+        data: StateData<'_, GameData<'_, '_>>,
+        event: StateEvent,
+    ) -> SimpleTrans {
+        let StateData { world, .. } = data;
+
+        let button = match event {
+            StateEvent::Input(InputEvent::ButtonPressed(button)) => button,
+            _ => return Trans::None,
+        };
+        // Every key press is also reported by scan code, which we don't bind.
+        if let Button::ScanCode(_) | Button::MouseWheel { .. } = button {
+            return Trans::None;
+        }
+
+        if self.capturing {
+            self.capturing = false;
+            if button != Button::Key(VirtualKeyCode::Escape) {
+                let message = self.rebind(world, button);
+                self.set_status(world, message);
+            } else {
+                self.set_status(world, String::new());
+            }
+            self.refresh(world);
+            return Trans::None;
+        }
+
+        let selected_kind = self
+            .entries
+            .get(self.selected)
+            .map(|entry| entry.kind.clone());
+        match (button, selected_kind) {
+            (Button::Key(VirtualKeyCode::Up), _) if self.selected > 0 => self.selected -= 1,
+            (Button::Key(VirtualKeyCode::Down), _) if self.selected + 1 < self.entries.len() => {
+                self.selected += 1
+            }
+            (Button::Key(VirtualKeyCode::Return), Some(SettingKind::Binding(target))) => {
+                self.capturing = true;
+                let message = format!(
+                    "Press a key or button for {}, Esc to cancel",
+                    target_name(&target)
+                );
+                self.set_status(world, message);
+            }
+            (Button::Key(VirtualKeyCode::Return), Some(SettingKind::Volume(channel))) => {
+                toggle_mute(&mut world.write_resource::<AudioSettings>(), channel);
+            }
+            (Button::Key(VirtualKeyCode::Left), Some(SettingKind::Volume(channel))) => {
+                adjust_volume(
+                    &mut world.write_resource::<AudioSettings>(),
+                    channel,
+                    -VOLUME_STEP,
+                );
+            }
+            (Button::Key(VirtualKeyCode::Right), Some(SettingKind::Volume(channel))) => {
+                adjust_volume(
+                    &mut world.write_resource::<AudioSettings>(),
+                    channel,
+                    VOLUME_STEP,
+                );
+            }
+            (Button::Key(VirtualKeyCode::Escape), _) => {
+                // Controllers handed to the paddles while playing are not part of the bindings.
+                let input = world.read_resource::<InputHandler<StringBindings>>();
+                let bindings = world
+                    .read_resource::<ControllerAssignments>()
+                    .configured_bindings(&input.bindings);
+                if let Err(e) = crate::bindings::save(world, &bindings) {
+                    log::error!("Failed to save the bindings: {}", e);
+                    world
+                        .write_resource::<Toasts>()
+                        .push("Failed to save the bindings, see the log");
+                }
+                world.read_resource::<AudioSettings>().save();
+                return Trans::Pop;
+            }
+            _ => {}
+        }
+        self.refresh(world);
+
+        Trans::None
+    }
+}
+
+impl Settings {
+    /// Binds `button` to the selected entry, and returns the message to show.
+    fn rebind(&mut self, world: &mut World, button: Button) -> String {
+        let target = match &self.entries[self.selected].kind {
+            SettingKind::Binding(target) => target.clone(),
+            SettingKind::Volume(_) => return String::new(),
+        };
+        let mut input = world.write_resource::<InputHandler<StringBindings>>();
+
+        let conflict = self
+            .entries
+            .iter()
+            .filter_map(|entry| match &entry.kind {
+                SettingKind::Binding(other) if *other != target => Some(other),
+                _ => None,
+            })
+            .find(|other| bound_button(&input.bindings, other) == Some(button));
+        if let Some(conflict) = conflict {
+            return format!(
+                "{} is already bound to {}",
+                button_name(&button),
+                target_name(conflict)
+            );
+        }
+
+        match apply_binding(&mut input.bindings, &target, button) {
+            Ok(()) => format!("{} bound to {}", target_name(&target), button_name(&button)),
+            Err(message) => message,
+        }
+    }
+
+    fn set_status(&self, world: &mut World, message: String) {
+        if let Some(status) = self.status {
+            if let Some(text) = world.write_storage::<UiText>().get_mut(status) {
+                text.text = message;
+            }
+        }
+    }
+
+    /// Updates the entry texts to show the current settings and selection.
+    fn refresh(&self, world: &mut World) {
+        let input = world.read_resource::<InputHandler<StringBindings>>();
+        let audio_settings = world.read_resource::<AudioSettings>();
+        let mut texts = world.write_storage::<UiText>();
+
+        for (index, entry) in self.entries.iter().enumerate() {
+            if let Some(text) = texts.get_mut(entry.entity) {
+                let selected = index == self.selected;
+                text.text = match &entry.kind {
+                    SettingKind::Binding(target) if selected && self.capturing => {
+                        format!("{}: ...", target_name(target))
+                    }
+                    SettingKind::Binding(target) => {
+                        let button = bound_button(&input.bindings, target)
+                            .map(|button| button_name(&button))
+                            .unwrap_or_else(|| "-".to_string());
+                        format!("{}: {}", target_name(target), button)
+                    }
+                    SettingKind::Volume(channel) => volume_text(&audio_settings, *channel),
+                };
+                text.color = if selected {
+                    COLOUR_SELECTED
+                } else {
+                    COLOUR_NORMAL
+                };
+            }
+        }
+    }
+}
+
+fn volume_text(audio_settings: &AudioSettings, channel: VolumeChannel) -> String {
+    let (name, volume, muted) = match channel {
+        VolumeChannel::Master => ("Master volume", audio_settings.master, audio_settings.muted),
+        VolumeChannel::Music => (
+            "Music volume",
+            audio_settings.music,
+            audio_settings.music_muted,
+        ),
+        VolumeChannel::Effects => (
+            "Effects volume",
+            audio_settings.effects,
+            audio_settings.effects_muted,
+        ),
+    };
+    if muted {
+        format!("{}: {:.0}% (muted)", name, volume * 100.0)
+    } else {
+        format!("{}: {:.0}%", name, volume * 100.0)
+    }
+}
+
+fn toggle_mute(audio_settings: &mut AudioSettings, channel: VolumeChannel) {
+    let muted = match channel {
+        VolumeChannel::Master => &mut audio_settings.muted,
+        VolumeChannel::Music => &mut audio_settings.music_muted,
+        VolumeChannel::Effects => &mut audio_settings.effects_muted,
+    };
+    *muted = !*muted;
+}
+
+fn adjust_volume(audio_settings: &mut AudioSettings, channel: VolumeChannel, delta: f32) {
+    match channel {
+        VolumeChannel::Master => audio_settings.adjust_master(delta),
+        VolumeChannel::Music => {
+            audio_settings.music = step_volume(audio_settings.music, delta);
+            audio_settings.music_muted = false;
+        }
+        VolumeChannel::Effects => {
+            audio_settings.effects = step_volume(audio_settings.effects, delta);
+            audio_settings.effects_muted = false;
+        }
+    }
+}
+
+/// Returns the bindings that can be changed, in display order.
+fn binding_targets(bindings: &Bindings<StringBindings>) -> Vec<BindingTarget> {
+    let mut axes = bindings
+        .axes()
+        .filter(|axis| {
+            bindings
+                .axis(axis.as_str())
+                .and_then(emulated_buttons)
+                .is_some()
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+    axes.sort();
+    let mut actions = bindings.actions().cloned().collect::<Vec<_>>();
+    actions.sort();
+
+    axes.into_iter()
+        .flat_map(|axis| {
+            vec![
+                BindingTarget::AxisPos(axis.clone()),
+                BindingTarget::AxisNeg(axis),
+            ]
+        })
+        .chain(actions.into_iter().map(BindingTarget::Action))
+        .collect()
+}
+
+fn target_name(target: &BindingTarget) -> String {
+    match target {
+        BindingTarget::AxisPos(axis) => format!("{} up", axis.replace('_', " ")),
+        BindingTarget::AxisNeg(axis) => format!("{} down", axis.replace('_', " ")),
+        BindingTarget::Action(action) => action.replace('_', " "),
+    }
+}
+
+/// Returns the button currently bound to the target.
+fn bound_button(bindings: &Bindings<StringBindings>, target: &BindingTarget) -> Option<Button> {
+    match target {
+        BindingTarget::AxisPos(axis) => bindings
+            .axis(axis.as_str())
+            .and_then(emulated_buttons)
+            .map(|(pos, _)| pos),
+        BindingTarget::AxisNeg(axis) => bindings
+            .axis(axis.as_str())
+            .and_then(emulated_buttons)
+            .map(|(_, neg)| neg),
+        BindingTarget::Action(action) => bindings
+            .action_bindings(action.as_str())
+            .next()
+            .and_then(|combination| combination.first().copied()),
+    }
+}
+
+/// Binds `button` to the target, leaving the bindings untouched on failure.
+fn apply_binding(
+    bindings: &mut Bindings<StringBindings>,
+    target: &BindingTarget,
+    button: Button,
+) -> Result<(), String> {
+    match target {
+        BindingTarget::AxisPos(axis_name) | BindingTarget::AxisNeg(axis_name) => {
+            let axis = match bindings.axis(axis_name.as_str()) {
+                Some(axis) => axis.clone(),
+                None => return Err(format!("`{}` is not bound", axis_name)),
+            };
+            let (pos, neg) = emulated_buttons(&axis)
+                .ok_or_else(|| format!("`{}` has no keyboard binding", axis_name))?;
+            let rebound = match target {
+                BindingTarget::AxisPos(_) => with_emulated_buttons(&axis, button, neg),
+                _ => with_emulated_buttons(&axis, pos, button),
+            };
+
+            let _ = bindings.remove_axis(axis_name.as_str());
+            bindings
+                .insert_axis(axis_name.clone(), rebound)
+                .map_err(|e| {
+                    let _ = bindings.insert_axis(axis_name.clone(), axis);
+                    format!("Cannot bind {}: {}", button_name(&button), e)
+                })
+                .map(|_| ())
+        }
+        BindingTarget::Action(action) => {
+            let previous = bindings
+                .action_bindings(action.as_str())
+                .next()
+                .map(|combination| combination.to_vec());
+            if let Some(previous) = previous.as_ref() {
+                bindings.remove_action_binding(action.as_str(), previous);
+            }
+            bindings
+                .insert_action_binding(action.clone(), vec![button])
+                .map_err(|e| {
+                    if let Some(previous) = previous {
+                        let _ = bindings.insert_action_binding(action.clone(), previous);
+                    }
+                    format!("Cannot bind {}: {}", button_name(&button), e)
+                })
+        }
+    }
+}
+
+fn create_text(world: &mut World, font: FontHandle, id: &str, y: f32, text: &str) -> Entity {
+    let transform = UiTransform::new(
+        id.to_string(),
+        Anchor::TopMiddle,
+        Anchor::Middle,
+        0.,
+        y,
+        2.,
+        600.,
+        30.,
+    );
+
+    world
+        .create_entity()
+        .with(ArenaUi::new(&transform, 20.))
+        .with(transform)
+        .with(UiText::new(font, text.to_string(), COLOUR_NORMAL, 20.))
+        .build()
+}
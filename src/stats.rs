@@ -0,0 +1,267 @@
+//! Statistics of the match in play.
+
+use serde::Serialize;
+use std::fmt;
+
+use crate::{
+    event::{PongEvent, Surface},
+    Side,
+};
+
+/// Statistics of the match in play, kept up to date by the `MatchStatsSystem` and reset when a
+/// new match starts.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct MatchStats {
+    /// Paddle hits of the rally in play, since the last serve. `None` before the first serve, as
+    /// a goal with no serve before it, such as at the start of an event log, is no rally.
+    pub rally: Option<u32>,
+    /// Rallies that ended in a goal.
+    pub rallies: u32,
+    pub longest_rally: u32,
+    /// Paddle hits of every finished rally, to work out the average.
+    pub rally_hits: u32,
+    pub left_hits: u32,
+    pub right_hits: u32,
+    /// Fastest the ball went, in world units per second.
+    pub top_speed: f32,
+    /// Seconds the ball spent in the left and right halves of the arena.
+    pub left_half_seconds: f32,
+    pub right_half_seconds: f32,
+    /// Goals scored by each side, by the third of the arena they went in at: bottom, middle
+    /// and top.
+    pub left_goals: [u32; 3],
+    pub right_goals: [u32; 3],
+}
+
+impl MatchStats {
+    /// Counts the event of a match played in an arena `arena_height` high.
+    pub fn record(&mut self, event: &PongEvent, arena_height: f32) {
+        if let Some((_, velocity)) = event.ball_motion() {
+            self.top_speed = self.top_speed.max(velocity[0].hypot(velocity[1]));
+        }
+        match *event {
+            PongEvent::Serve { .. } => self.rally = Some(0),
+            PongEvent::Bounce { surface, .. } if surface.is_paddle() => {
+                if let Some(hits) = self.rally.as_mut() {
+                    *hits += 1;
+                }
+                if surface == Surface::LeftPaddle {
+                    self.left_hits += 1;
+                } else {
+                    self.right_hits += 1;
+                }
+            }
+            PongEvent::Score { side, position, .. } => {
+                if let Some(hits) = self.rally.take() {
+                    self.rallies += 1;
+                    self.rally_hits += hits;
+                    self.longest_rally = self.longest_rally.max(hits);
+                }
+
+                let third = ((position[1] / arena_height * 3.0).max(0.0) as usize).min(2);
+                match side {
+                    Side::Left => self.left_goals[third] += 1,
+                    Side::Right => self.right_goals[third] += 1,
+                }
+            }
+            PongEvent::Bounce { .. } | PongEvent::GameOver { .. } => {}
+        }
+    }
+
+    /// Counts `seconds` of the ball being at `x` in an arena `arena_width` wide.
+    pub fn track_ball(&mut self, x: f32, arena_width: f32, seconds: f32) {
+        if x < arena_width / 2.0 {
+            self.left_half_seconds += seconds;
+        } else {
+            self.right_half_seconds += seconds;
+        }
+    }
+
+    /// Returns the average paddle hits of the finished rallies.
+    pub fn average_rally(&self) -> f32 {
+        match self.rallies {
+            0 => 0.0,
+            rallies => self.rally_hits as f32 / rallies as f32,
+        }
+    }
+
+    /// Returns the statistics as JSON, with the average rally.
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        #[derive(Serialize)]
+        struct Export<'a> {
+            #[serde(flatten)]
+            stats: &'a MatchStats,
+            average_rally: f32,
+        }
+
+        serde_json::to_string_pretty(&Export {
+            stats: self,
+            average_rally: self.average_rally(),
+        })
+    }
+}
+
+impl fmt::Display for MatchStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Rallies: {}, longest {} hits, average {:.1} hits",
+            self.rallies,
+            self.longest_rally,
+            self.average_rally()
+        )?;
+        writeln!(
+            f,
+            "Paddle hits: left {}, right {}",
+            self.left_hits, self.right_hits
+        )?;
+        writeln!(f, "Top speed: {:.0}", self.top_speed)?;
+        // Event logs only have the ball when something happens, so they leave this out.
+        let possession = self.left_half_seconds + self.right_half_seconds;
+        if possession > 0.0 {
+            writeln!(
+                f,
+                "Ball in each half: left {:.0}%, right {:.0}%",
+                self.left_half_seconds / possession * 100.0,
+                self.right_half_seconds / possession * 100.0
+            )?;
+        }
+        for (side, goals) in &[("Left", self.left_goals), ("Right", self.right_goals)] {
+            writeln!(
+                f,
+                "{} goals: top {}, middle {}, bottom {}",
+                side, goals[2], goals[1], goals[0]
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ARENA_HEIGHT: f32 = 90.0;
+
+    fn serve() -> PongEvent {
+        PongEvent::Serve {
+            ball: None,
+            position: [50.0, 45.0],
+            velocity: [3.0, 4.0],
+            frame: 0,
+        }
+    }
+
+    fn bounce(surface: Surface) -> PongEvent {
+        PongEvent::Bounce {
+            ball: None,
+            surface,
+            position: [50.0, 45.0],
+            velocity: [-6.0, 8.0],
+            speed: 6.0,
+            frame: 0,
+        }
+    }
+
+    fn goal(side: Side, height: f32) -> PongEvent {
+        PongEvent::Score {
+            ball: None,
+            side,
+            position: [0.0, height],
+            velocity: [-3.0, 4.0],
+            score_left: 0,
+            score_right: 0,
+            frame: 0,
+        }
+    }
+
+    fn stats_of(events: &[PongEvent]) -> MatchStats {
+        let mut stats = MatchStats::default();
+        for event in events {
+            stats.record(event, ARENA_HEIGHT);
+        }
+        stats
+    }
+
+    #[test]
+    fn counts_paddle_hits_from_serve_to_goal() {
+        let stats = stats_of(&[
+            serve(),
+            bounce(Surface::LeftPaddle),
+            bounce(Surface::TopWall),
+            bounce(Surface::RightPaddle),
+            bounce(Surface::LeftPaddle),
+            goal(Side::Left, 45.0),
+            serve(),
+            bounce(Surface::BottomWall),
+            goal(Side::Right, 45.0),
+        ]);
+
+        assert_eq!(stats.rallies, 2);
+        assert_eq!(stats.longest_rally, 3);
+        assert_eq!(stats.rally_hits, 3);
+        assert_eq!(stats.average_rally(), 1.5);
+        assert_eq!((stats.left_hits, stats.right_hits), (2, 1));
+        assert_eq!(stats.rally, None);
+        assert_eq!(stats.top_speed, 10.0);
+    }
+
+    #[test]
+    fn goals_without_a_serve_are_no_rally() {
+        let stats = stats_of(&[
+            bounce(Surface::RightPaddle),
+            goal(Side::Left, 45.0),
+            serve(),
+            bounce(Surface::LeftPaddle),
+        ]);
+
+        assert_eq!(stats.rallies, 0);
+        assert_eq!(stats.average_rally(), 0.0);
+        assert_eq!(stats.right_hits, 1);
+        assert_eq!(stats.left_goals, [0, 1, 0]);
+        assert_eq!(stats.rally, Some(1));
+    }
+
+    #[test]
+    fn places_goals_by_height_up_to_the_edges() {
+        let stats = stats_of(&[
+            goal(Side::Left, -5.0),
+            goal(Side::Left, 0.0),
+            goal(Side::Left, 29.9),
+            goal(Side::Left, 30.0),
+            goal(Side::Left, 59.9),
+            goal(Side::Right, 60.0),
+            goal(Side::Right, 90.0),
+            goal(Side::Right, 95.0),
+        ]);
+
+        assert_eq!(stats.left_goals, [3, 2, 0]);
+        assert_eq!(stats.right_goals, [0, 0, 3]);
+    }
+
+    #[test]
+    fn json_has_every_field_and_the_average_rally() {
+        let stats = stats_of(&[
+            serve(),
+            bounce(Surface::LeftPaddle),
+            goal(Side::Right, 80.0),
+        ]);
+        let json: serde_json::Value = serde_json::from_str(&stats.to_json().unwrap()).unwrap();
+
+        assert_eq!(json["rallies"], 1);
+        assert_eq!(json["longest_rally"], 1);
+        assert_eq!(json["average_rally"], 1.0);
+        assert_eq!(json["left_hits"], 1);
+        assert_eq!(json["top_speed"], 10.0);
+        assert_eq!(json["right_goals"], serde_json::json!([0, 0, 1]));
+        assert!(json["rally"].is_null());
+        for field in &[
+            "rally_hits",
+            "right_hits",
+            "left_half_seconds",
+            "right_half_seconds",
+        ] {
+            assert!(json.get(field).is_some(), "missing `{}`", field);
+        }
+    }
+}
@@ -0,0 +1,269 @@
+//! Persistence of settings and progress as RON.
+//!
+//! Natively, values are stored in files relative to the application root. On wasm, they are
+//! stored in the browser's `localStorage`, keyed by the same relative path.
+//!
+//! The layout of the stored values is versioned. `migrate` brings values stored by an older
+//! version of the game up to date, and runs before anything is loaded.
+
+use amethyst::Error;
+use serde::{de::DeserializeOwned, Serialize};
+#[cfg(not(feature = "wasm"))]
+use std::path::PathBuf;
+
+/// Version of the layout of the stored values.
+pub const STORAGE_VERSION: u32 = 1;
+
+/// Key of the version of the stored values.
+const VERSION_KEY: &str = "save/version.ron";
+
+/// Migrations between versions, the one at index `n` migrating from version `n` to `n + 1`.
+const MIGRATIONS: &[fn(&dyn Storage) -> Result<(), Error>] = &[migrate_from_unversioned];
+
+/// Place values are stored in, keyed by their path relative to the application root.
+pub trait Storage {
+    /// Returns the value stored at `key`, `None` when nothing is stored.
+    fn read(&self, key: &str) -> Result<Option<String>, Error>;
+
+    fn write(&self, key: &str, value: &str) -> Result<(), Error>;
+
+    /// Removes the value stored at `key`, if any.
+    fn remove(&self, key: &str) -> Result<(), Error>;
+}
+
+/// Stores values in files under a root directory.
+#[cfg(not(feature = "wasm"))]
+pub struct FileStorage {
+    root: PathBuf,
+}
+
+#[cfg(not(feature = "wasm"))]
+impl FileStorage {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        FileStorage { root: root.into() }
+    }
+}
+
+#[cfg(not(feature = "wasm"))]
+impl Storage for FileStorage {
+    fn read(&self, key: &str) -> Result<Option<String>, Error> {
+        let path = self.root.join(key);
+        if path.exists() {
+            Ok(Some(std::fs::read_to_string(path)?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn write(&self, key: &str, value: &str) -> Result<(), Error> {
+        let path = self.root.join(key);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, value)?;
+        Ok(())
+    }
+
+    fn remove(&self, key: &str) -> Result<(), Error> {
+        let path = self.root.join(key);
+        if path.exists() {
+            std::fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+}
+
+/// Stores values in the browser's `localStorage`, with keys prefixed by `pong/`.
+#[cfg(feature = "wasm")]
+pub struct LocalStorage {
+    storage: web_sys::Storage,
+}
+
+#[cfg(feature = "wasm")]
+impl LocalStorage {
+    pub fn new() -> Result<Self, Error> {
+        web_sys::window()
+            .and_then(|window| window.local_storage().ok().flatten())
+            .map(|storage| LocalStorage { storage })
+            .ok_or_else(|| Error::from_string("`localStorage` is not available"))
+    }
+
+    fn storage_key(key: &str) -> String {
+        format!("pong/{}", key)
+    }
+}
+
+#[cfg(feature = "wasm")]
+impl Storage for LocalStorage {
+    fn read(&self, key: &str) -> Result<Option<String>, Error> {
+        self.storage
+            .get_item(&Self::storage_key(key))
+            .map_err(|e| Error::from_string(format!("Failed to read `{}`: {:?}", key, e)))
+    }
+
+    fn write(&self, key: &str, value: &str) -> Result<(), Error> {
+        self.storage
+            .set_item(&Self::storage_key(key), value)
+            .map_err(|e| Error::from_string(format!("Failed to store `{}`: {:?}", key, e)))
+    }
+
+    fn remove(&self, key: &str) -> Result<(), Error> {
+        self.storage
+            .remove_item(&Self::storage_key(key))
+            .map_err(|e| Error::from_string(format!("Failed to remove `{}`: {:?}", key, e)))
+    }
+}
+
+/// Returns the storage of the platform the game runs on.
+#[cfg(not(feature = "wasm"))]
+pub fn platform() -> Result<FileStorage, Error> {
+    Ok(FileStorage::new(amethyst::utils::application_root_dir()?))
+}
+
+/// Returns the storage of the platform the game runs on.
+#[cfg(feature = "wasm")]
+pub fn platform() -> Result<LocalStorage, Error> {
+    LocalStorage::new()
+}
+
+/// Saves the value as RON at the given path.
+pub fn save<T: Serialize>(path: &str, value: &T) -> Result<(), Error> {
+    save_to(&platform()?, path, value)
+}
+
+/// Loads the value saved at the given path, `None` when nothing has been saved.
+pub fn load<T: DeserializeOwned>(path: &str) -> Result<Option<T>, Error> {
+    load_from(&platform()?, path)
+}
+
+/// Removes the value saved at the given path.
+pub fn remove(path: &str) -> Result<(), Error> {
+    platform()?.remove(path)
+}
+
+/// Saves the value as RON in `storage`.
+pub fn save_to<T: Serialize>(storage: &dyn Storage, key: &str, value: &T) -> Result<(), Error> {
+    let serialized = ron::ser::to_string_pretty(value, Default::default())?;
+    storage.write(key, &serialized)
+}
+
+/// Loads the value stored as RON in `storage`, `None` when nothing is stored.
+pub fn load_from<T: DeserializeOwned>(
+    storage: &dyn Storage,
+    key: &str,
+) -> Result<Option<T>, Error> {
+    match storage.read(key)? {
+        Some(serialized) => Ok(Some(ron::de::from_str(&serialized)?)),
+        None => Ok(None),
+    }
+}
+
+/// Migrates the stored values to `STORAGE_VERSION`, recording the version after each step.
+///
+/// Values stored by a newer version of the game are left alone.
+pub fn migrate(storage: &dyn Storage) -> Result<(), Error> {
+    let mut version = load_from::<u32>(storage, VERSION_KEY)?.unwrap_or(0);
+    while version < STORAGE_VERSION {
+        let migration = MIGRATIONS.get(version as usize).ok_or_else(|| {
+            Error::from_string(format!("No migration from storage version {}", version))
+        })?;
+        migration(storage)?;
+        version += 1;
+        save_to(storage, VERSION_KEY, &version)?;
+    }
+    Ok(())
+}
+
+/// Version 0 stored the same values, only without a version.
+fn migrate_from_unversioned(_storage: &dyn Storage) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Migrates the storage of the platform, logging failures.
+pub fn migrate_platform() {
+    if let Err(e) = platform().and_then(|storage| migrate(&storage)) {
+        log::error!("Failed to migrate saved settings: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{cell::RefCell, collections::HashMap};
+
+    /// Keeps values in memory.
+    #[derive(Default)]
+    struct MemoryStorage {
+        values: RefCell<HashMap<String, String>>,
+    }
+
+    impl Storage for MemoryStorage {
+        fn read(&self, key: &str) -> Result<Option<String>, Error> {
+            Ok(self.values.borrow().get(key).cloned())
+        }
+
+        fn write(&self, key: &str, value: &str) -> Result<(), Error> {
+            self.values
+                .borrow_mut()
+                .insert(key.to_string(), value.to_string());
+            Ok(())
+        }
+
+        fn remove(&self, key: &str) -> Result<(), Error> {
+            self.values.borrow_mut().remove(key);
+            Ok(())
+        }
+    }
+
+    fn round_trip(storage: &dyn Storage) {
+        assert_eq!(
+            load_from::<Vec<i32>>(storage, "save/scores.ron").unwrap(),
+            None
+        );
+
+        save_to(storage, "save/scores.ron", &vec![3, 11]).unwrap();
+        assert_eq!(
+            load_from::<Vec<i32>>(storage, "save/scores.ron").unwrap(),
+            Some(vec![3, 11])
+        );
+
+        storage.remove("save/scores.ron").unwrap();
+        assert_eq!(
+            load_from::<Vec<i32>>(storage, "save/scores.ron").unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn memory_storage_round_trips_values() {
+        round_trip(&MemoryStorage::default());
+    }
+
+    #[test]
+    fn file_storage_round_trips_values() {
+        let root = std::env::temp_dir().join(format!("pong_storage_{}", std::process::id()));
+        round_trip(&FileStorage::new(&root));
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn migrate_records_the_current_version() {
+        let storage = MemoryStorage::default();
+        migrate(&storage).unwrap();
+        assert_eq!(
+            load_from::<u32>(&storage, VERSION_KEY).unwrap(),
+            Some(STORAGE_VERSION)
+        );
+    }
+
+    #[test]
+    fn migrate_leaves_newer_versions_alone() {
+        let storage = MemoryStorage::default();
+        save_to(&storage, VERSION_KEY, &(STORAGE_VERSION + 1)).unwrap();
+        migrate(&storage).unwrap();
+        assert_eq!(
+            load_from::<u32>(&storage, VERSION_KEY).unwrap(),
+            Some(STORAGE_VERSION + 1)
+        );
+    }
+}
@@ -1,6 +1,7 @@
 use crate::{
-    audio::{play_bounce, play_score, Sounds},
+    audio::{play_event, AudioSettings, ImpactSounds, Sounds},
     event::PongEvent,
+    game_config::GameConfig,
 };
 
 use amethyst::{
@@ -21,25 +22,43 @@ impl<'s> System<'s> for AudioSystem {
     type SystemData = (
         Read<'s, AssetStorage<Source>>,
         Option<Read<'s, Sounds>>,
+        Option<Read<'s, ImpactSounds>>,
         Option<Read<'s, Output>>,
         Read<'s, EventChannel<PongEvent>>,
+        Read<'s, AudioSettings>,
+        Read<'s, GameConfig>,
     );
 
-    fn run(&mut self, (storage, sounds, audio_output, pong_events): Self::SystemData) {
+    fn run(
+        &mut self,
+        (
+            storage,
+            sounds,
+            impact_sounds,
+            audio_output,
+            pong_events,
+            audio_settings,
+            game_config,
+        ): Self::SystemData,
+    ) {
         // Reads PongEvent, play sound accordingly
         let reader = self
             .pong_event_reader
             .as_mut()
             .expect("AudioSystem::setup has not been called");
 
+        let volume = audio_settings.effects_volume();
         if let Some(sounds) = sounds {
-            pong_events.read(reader).for_each(|ev| match ev {
-                PongEvent::Bounce => {
-                    play_bounce(&*sounds, &storage, audio_output.as_ref().map(|o| o.deref()))
-                }
-                PongEvent::Score => {
-                    play_score(&*sounds, &storage, audio_output.as_ref().map(|o| o.deref()))
-                }
+            pong_events.read(reader).for_each(|ev| {
+                play_event(
+                    ev,
+                    &*sounds,
+                    impact_sounds.as_ref().map(|s| s.deref()),
+                    &storage,
+                    audio_output.as_ref().map(|o| o.deref()),
+                    volume,
+                    &game_config,
+                )
             });
         }
     }
@@ -0,0 +1,72 @@
+use crate::{
+    audio::{AudioSettings, VOLUME_STEP},
+    toast::Toasts,
+};
+use amethyst::{
+    ecs::{Read, System, SystemData, World, Write},
+    input::{InputEvent, StringBindings},
+    shrev::{EventChannel, ReaderId},
+};
+
+/// This system is responsible for the mute and volume actions.
+#[derive(Default)]
+pub struct AudioSettingsSystem {
+    input_event_reader: Option<ReaderId<InputEvent<StringBindings>>>,
+}
+
+impl<'s> System<'s> for AudioSettingsSystem {
+    type SystemData = (
+        Read<'s, EventChannel<InputEvent<StringBindings>>>,
+        Write<'s, AudioSettings>,
+        Write<'s, Toasts>,
+    );
+
+    fn run(&mut self, (input_events, mut audio_settings, mut toasts): Self::SystemData) {
+        let reader = self
+            .input_event_reader
+            .as_mut()
+            .expect("AudioSettingsSystem::setup has not been called");
+
+        let mut changed = false;
+        for event in input_events.read(reader) {
+            if let InputEvent::ActionPressed(action) = event {
+                match action.as_str() {
+                    "toggle_mute" => {
+                        audio_settings.muted = !audio_settings.muted;
+                        toasts.push(if audio_settings.muted {
+                            "Audio muted"
+                        } else {
+                            "Audio unmuted"
+                        });
+                    }
+                    "volume_up" => {
+                        audio_settings.adjust_master(VOLUME_STEP);
+                        toasts.push(volume_message(&audio_settings));
+                    }
+                    "volume_down" => {
+                        audio_settings.adjust_master(-VOLUME_STEP);
+                        toasts.push(volume_message(&audio_settings));
+                    }
+                    _ => continue,
+                }
+                changed = true;
+            }
+        }
+        if changed {
+            audio_settings.save();
+        }
+    }
+
+    fn setup(&mut self, world: &mut World) {
+        Self::SystemData::setup(world);
+        self.input_event_reader = Some(
+            world
+                .fetch_mut::<EventChannel<InputEvent<StringBindings>>>()
+                .register_reader(),
+        );
+    }
+}
+
+fn volume_message(audio_settings: &AudioSettings) -> String {
+    format!("Volume {:.0}%", audio_settings.master * 100.0)
+}
@@ -1,8 +1,12 @@
-use crate::{event::PongEvent, Ball, Paddle, Side};
+use crate::{
+    event::{PongEvent, Surface},
+    game_config::GameConfig,
+    Ball, Paddle, Side,
+};
 use amethyst::{
-    core::transform::Transform,
+    core::{timing::Time, transform::Transform},
     derive::SystemDesc,
-    ecs::prelude::{Join, ReadStorage, System, SystemData, Write, WriteStorage},
+    ecs::prelude::{Entities, Join, Read, ReadStorage, System, SystemData, Write, WriteStorage},
     shrev::EventChannel,
 };
 
@@ -13,29 +17,47 @@ pub struct BounceSystem;
 
 impl<'s> System<'s> for BounceSystem {
     type SystemData = (
+        Entities<'s>,
         WriteStorage<'s, Ball>,
         ReadStorage<'s, Paddle>,
         ReadStorage<'s, Transform>,
         Write<'s, EventChannel<PongEvent>>,
+        Read<'s, GameConfig>,
+        Read<'s, Time>,
     );
 
-    fn run(&mut self, (mut balls, paddles, transforms, mut pong_events): Self::SystemData) {
+    fn run(
+        &mut self,
+        (entities, mut balls, paddles, transforms, mut pong_events, game_config, time): Self::SystemData,
+    ) {
         // Check whether a ball collided, and bounce off accordingly.
         //
         // We also check for the velocity of the ball every time, to prevent multiple collisions
         // from occurring.
-        for (ball, transform) in (&mut balls, &transforms).join() {
-            use crate::ARENA_HEIGHT;
-
+        for (entity, ball, transform) in (&entities, &mut balls, &transforms).join() {
+            let arena_height = game_config.arena.height;
             let ball_x = transform.translation().x;
             let ball_y = transform.translation().y;
 
             // Bounce at the top or the bottom of the arena.
-            if (ball_y <= ball.radius && ball.velocity[1] < 0.0)
-                || (ball_y >= ARENA_HEIGHT - ball.radius && ball.velocity[1] > 0.0)
-            {
+            let wall = if ball_y <= ball.radius && ball.velocity[1] < 0.0 {
+                Some(Surface::BottomWall)
+            } else if ball_y >= arena_height - ball.radius && ball.velocity[1] > 0.0 {
+                Some(Surface::TopWall)
+            } else {
+                None
+            };
+            if let Some(surface) = wall {
+                let speed = ball.velocity[1].abs();
                 ball.velocity[1] = -ball.velocity[1];
-                pong_events.single_write(PongEvent::Bounce);
+                pong_events.single_write(PongEvent::Bounce {
+                    ball: Some(entity),
+                    surface,
+                    position: [ball_x, ball_y],
+                    velocity: ball.velocity,
+                    speed,
+                    frame: time.frame_number(),
+                });
             }
 
             // Bounce at the paddles.
@@ -58,8 +80,20 @@ impl<'s> System<'s> for BounceSystem {
                 ) && ((paddle.side == Side::Left && ball.velocity[0] < 0.0)
                     || (paddle.side == Side::Right && ball.velocity[0] > 0.0))
                 {
+                    let surface = match paddle.side {
+                        Side::Left => Surface::LeftPaddle,
+                        Side::Right => Surface::RightPaddle,
+                    };
+                    let speed = ball.velocity[0].abs();
                     ball.velocity[0] = -ball.velocity[0];
-                    pong_events.single_write(PongEvent::Bounce);
+                    pong_events.single_write(PongEvent::Bounce {
+                        ball: Some(entity),
+                        surface,
+                        position: [ball_x, ball_y],
+                        velocity: ball.velocity,
+                        speed,
+                        frame: time.frame_number(),
+                    });
                 }
             }
         }
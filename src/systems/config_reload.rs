@@ -0,0 +1,198 @@
+use crate::{
+    audio::AudioSettings, game_config::GameConfig, layered_config::ConfigLoader, session::Session,
+    systems::ControllerAssignments, toast::Toasts, Ball, Paddle,
+};
+use amethyst::{
+    core::timing::Time,
+    ecs::{Join, Read, System, Write, WriteStorage},
+    input::{Bindings, InputHandler, StringBindings},
+    window::{DisplayConfig, Window},
+    Error,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+/// Seconds between checks of the config files for changes.
+const POLL_SECONDS: f64 = 1.0;
+
+/// The config files the game reads, with their layers.
+#[derive(Clone, Debug)]
+pub struct ConfigFiles {
+    pub display: ConfigLoader,
+    pub input: ConfigLoader,
+    pub input_profiles: ConfigLoader,
+    pub game: ConfigLoader,
+    pub audio: ConfigLoader,
+}
+
+impl ConfigFiles {
+    /// Returns the config files under `app_root`, with the game config and bindings read from
+    /// the given paths instead when set.
+    pub fn new(app_root: &Path, game: Option<PathBuf>, bindings: Option<PathBuf>) -> Self {
+        ConfigFiles {
+            display: ConfigLoader::new("display").with_file(app_root.join("config/display.ron")),
+            input: ConfigLoader::new("input").with_file(
+                bindings.unwrap_or_else(|| app_root.join(crate::bindings::BINDINGS_PATH)),
+            ),
+            input_profiles: ConfigLoader::new("input_profiles")
+                .with_file(app_root.join("config/input_profiles.ron")),
+            game: ConfigLoader::new("game").with_file(
+                game.unwrap_or_else(|| app_root.join(crate::game_config::GAME_CONFIG_PATH)),
+            ),
+            audio: ConfigLoader::new("audio")
+                .with_file(app_root.join(crate::audio::AUDIO_SETTINGS_PATH)),
+        }
+    }
+
+    /// Returns every value of every config, with where it comes from.
+    pub fn report(&self) -> Result<String, Error> {
+        fn section<T>(loader: &ConfigLoader) -> Result<String, Error>
+        where
+            T: Default + Serialize + DeserializeOwned,
+        {
+            let config = loader.load::<T>()?;
+            Ok(format!("[{}]\n{}", loader.name(), config.report()))
+        }
+
+        Ok([
+            section::<DisplayConfig>(&self.display)?,
+            section::<Bindings<StringBindings>>(&self.input)?,
+            section::<crate::input_profile::InputProfiles>(&self.input_profiles)?,
+            section::<GameConfig>(&self.game)?,
+            section::<AudioSettings>(&self.audio)?,
+        ]
+        .join("\n"))
+    }
+}
+
+/// This system watches the config files and applies changes to them while the game runs: the
+/// window title, the bindings, the paddle and ball tuning and the audio volumes. Other changes
+/// are applied on the next launch.
+#[derive(Default)]
+pub struct ConfigReloadSystem {
+    /// Last modification time of each watched file, `None` when it does not exist.
+    modified: HashMap<PathBuf, Option<SystemTime>>,
+    /// Time of the next check of the files.
+    next_poll: f64,
+}
+
+impl<'s> System<'s> for ConfigReloadSystem {
+    type SystemData = (
+        Option<Read<'s, ConfigFiles>>,
+        Read<'s, Time>,
+        Read<'s, Session>,
+        Option<Read<'s, Window>>,
+        Write<'s, InputHandler<StringBindings>>,
+        Write<'s, ControllerAssignments>,
+        Write<'s, GameConfig>,
+        Write<'s, AudioSettings>,
+        Write<'s, Toasts>,
+        WriteStorage<'s, Paddle>,
+        WriteStorage<'s, Ball>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            config_files,
+            time,
+            session,
+            window,
+            mut input,
+            mut assignments,
+            mut game_config,
+            mut audio_settings,
+            mut toasts,
+            mut paddles,
+            mut balls,
+        ): Self::SystemData,
+    ) {
+        let config_files = match config_files {
+            Some(config_files) => config_files,
+            None => return,
+        };
+        if time.absolute_real_time_seconds() < self.next_poll {
+            return;
+        }
+        self.next_poll = time.absolute_real_time_seconds() + POLL_SECONDS;
+
+        if self.changed(&config_files.display) {
+            if let Some(display) = reload::<DisplayConfig>(&config_files.display, &mut toasts) {
+                if let Some(window) = window.as_ref() {
+                    window.set_title(&display.title);
+                }
+            }
+        }
+        if self.changed(&config_files.input) {
+            if let Some(bindings) = reload(&config_files.input, &mut toasts) {
+                input.bindings = bindings;
+                // Controllers keep driving the paddles they were handed.
+                assignments.apply(&mut input);
+            }
+        }
+        if self.changed(&config_files.game) {
+            // Recorded and replayed matches must play with the config they started with.
+            if session.record.is_some() || session.replay.is_some() {
+                log::warn!("Ignoring changes to the game config while recording or replaying.");
+            } else if let Some(config) = reload::<GameConfig>(&config_files.game, &mut toasts) {
+                game_config.paddle = config.paddle;
+                game_config.ball = config.ball;
+                for paddle in (&mut paddles).join() {
+                    paddle.velocity = game_config.paddle.velocity;
+                    paddle.width = game_config.paddle.width;
+                    paddle.height = game_config.paddle.height;
+                }
+                for ball in (&mut balls).join() {
+                    ball.radius = game_config.ball.radius;
+                }
+            }
+        }
+        if self.changed(&config_files.audio) {
+            if let Some(settings) = reload(&config_files.audio, &mut toasts) {
+                *audio_settings = settings;
+            }
+        }
+    }
+}
+
+impl ConfigReloadSystem {
+    /// Returns whether any file of the config was modified, created or removed since the last
+    /// check. Files are only recorded on the first check.
+    fn changed(&mut self, loader: &ConfigLoader) -> bool {
+        let mut changed = false;
+        for path in loader.files() {
+            let modified = std::fs::metadata(&path)
+                .and_then(|metadata| metadata.modified())
+                .ok();
+            if let Some(previous) = self.modified.insert(path, modified) {
+                changed |= previous != modified;
+            }
+        }
+        changed
+    }
+}
+
+/// Loads the config again, keeping the current one when it is invalid.
+///
+/// Successful reloads are only logged, as the game saves the bindings and audio settings to
+/// their files itself.
+fn reload<T>(loader: &ConfigLoader, toasts: &mut Toasts) -> Option<T>
+where
+    T: Default + Serialize + DeserializeOwned,
+{
+    match loader.load::<T>() {
+        Ok(config) => {
+            log::info!("Reloaded the {} config.", loader.name());
+            Some(config.value)
+        }
+        Err(e) => {
+            log::error!("{}", e);
+            toasts.push(format!("Invalid {} config, see the log", loader.name()));
+            None
+        }
+    }
+}
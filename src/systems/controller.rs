@@ -0,0 +1,279 @@
+use crate::{toast::Toasts, Side};
+use amethyst::{
+    ecs::{Read, System, SystemData, World, Write},
+    input::{Axis, Bindings, ControllerAxis, InputEvent, InputHandler, StringBindings},
+    shrev::{EventChannel, ReaderId},
+};
+
+/// Dead zone used when the bindings don't specify a controller axis for a paddle.
+const DEFAULT_DEAD_ZONE: f64 = 0.2;
+
+/// Which controller drives each paddle, if any.
+#[derive(Debug, Default)]
+pub struct ControllerAssignments {
+    pub left: Option<u32>,
+    pub right: Option<u32>,
+    /// Controllers that are connected but have no paddle to drive.
+    pub spare: Vec<u32>,
+    /// Controller bindings of the paddle axes in the config, giving the stick, inversion and dead
+    /// zone of the controller assigned to each paddle.
+    left_template: Option<Axis>,
+    right_template: Option<Axis>,
+}
+
+impl ControllerAssignments {
+    /// Binds the assigned controllers in bindings freshly loaded from the config.
+    ///
+    /// Controller bindings in the config only describe how assigned controllers are read, so
+    /// they are taken out of the bindings, and a controller only drives the paddle it is
+    /// assigned to.
+    pub fn apply(&mut self, input: &mut InputHandler<StringBindings>) {
+        for &side in &[Side::Left, Side::Right] {
+            let template = input
+                .bindings
+                .axis(paddle_axis_name(side))
+                .cloned()
+                .and_then(|axis| split_controllers(axis).1);
+            *self.template_mut(side) = template;
+            let controller_id = *self.slot_mut(side);
+            assign(input, self, side, controller_id);
+        }
+    }
+
+    /// Returns the bindings as configured, with the controller bindings of the config in place of
+    /// the assigned controllers.
+    pub fn configured_bindings(
+        &self,
+        bindings: &Bindings<StringBindings>,
+    ) -> Bindings<StringBindings> {
+        let mut bindings = bindings.clone();
+        for &(side, template) in &[
+            (Side::Left, &self.left_template),
+            (Side::Right, &self.right_template),
+        ] {
+            let axis_name = paddle_axis_name(side);
+            let mut axes = match bindings.axis(axis_name).cloned() {
+                Some(axis) => split_controllers(axis).0,
+                None => continue,
+            };
+            axes.extend(template.clone());
+            let _ = bindings.remove_axis(axis_name);
+            if let Err(e) = bindings.insert_axis(axis_name, join_axes(axes)) {
+                log::error!("Failed to bind `{}`: {}", axis_name, e);
+            }
+        }
+        bindings
+    }
+
+    fn slot_mut(&mut self, side: Side) -> &mut Option<u32> {
+        match side {
+            Side::Left => &mut self.left,
+            Side::Right => &mut self.right,
+        }
+    }
+
+    fn template_mut(&mut self, side: Side) -> &mut Option<Axis> {
+        match side {
+            Side::Left => &mut self.left_template,
+            Side::Right => &mut self.right_template,
+        }
+    }
+
+    fn side_of(&self, controller_id: u32) -> Option<Side> {
+        if self.left == Some(controller_id) {
+            Some(Side::Left)
+        } else if self.right == Some(controller_id) {
+            Some(Side::Right)
+        } else {
+            None
+        }
+    }
+}
+
+/// This system is responsible for handing controllers to paddles as they connect and disconnect.
+///
+/// Each paddle axis keeps its keyboard bindings; the controller driving the paddle is bound
+/// alongside them.
+#[derive(Default)]
+pub struct ControllerAssignmentSystem {
+    input_event_reader: Option<ReaderId<InputEvent<StringBindings>>>,
+    /// Whether the controller bindings of the config have been taken over.
+    applied: bool,
+}
+
+impl<'s> System<'s> for ControllerAssignmentSystem {
+    type SystemData = (
+        Read<'s, EventChannel<InputEvent<StringBindings>>>,
+        Write<'s, InputHandler<StringBindings>>,
+        Write<'s, ControllerAssignments>,
+        Write<'s, Toasts>,
+    );
+
+    fn run(&mut self, (input_events, mut input, mut assignments, mut toasts): Self::SystemData) {
+        let reader = self
+            .input_event_reader
+            .as_mut()
+            .expect("ControllerAssignmentSystem::setup has not been called");
+        if !self.applied {
+            assignments.apply(&mut input);
+            self.applied = true;
+        }
+
+        for event in input_events.read(reader) {
+            match *event {
+                InputEvent::ControllerConnected { which } => {
+                    if assignments.side_of(which).is_some() || assignments.spare.contains(&which) {
+                        continue;
+                    }
+                    match free_side(&assignments) {
+                        Some(side) => {
+                            assign(&mut input, &mut assignments, side, Some(which));
+                            toasts.push(format!(
+                                "Controller {} controls the {} paddle",
+                                which,
+                                side_name(side)
+                            ));
+                        }
+                        None => {
+                            assignments.spare.push(which);
+                            toasts.push(format!(
+                                "Controller {} connected, both paddles are taken",
+                                which
+                            ));
+                        }
+                    }
+                }
+                InputEvent::ControllerDisconnected { which } => {
+                    assignments.spare.retain(|&spare| spare != which);
+                    let side = match assignments.side_of(which) {
+                        Some(side) => side,
+                        None => continue,
+                    };
+
+                    // Hand the paddle to a waiting controller, or back to the keyboard.
+                    if assignments.spare.is_empty() {
+                        assign(&mut input, &mut assignments, side, None);
+                        toasts.push(format!(
+                            "Controller {} disconnected, the {} paddle is back on the keyboard",
+                            which,
+                            side_name(side)
+                        ));
+                    } else {
+                        let spare = assignments.spare.remove(0);
+                        assign(&mut input, &mut assignments, side, Some(spare));
+                        toasts.push(format!(
+                            "Controller {} disconnected, controller {} controls the {} paddle",
+                            which,
+                            spare,
+                            side_name(side)
+                        ));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn setup(&mut self, world: &mut World) {
+        Self::SystemData::setup(world);
+        self.input_event_reader = Some(
+            world
+                .fetch_mut::<EventChannel<InputEvent<StringBindings>>>()
+                .register_reader(),
+        );
+    }
+}
+
+/// Returns the name of the input axis that moves the paddle on the given side.
+pub fn paddle_axis_name(side: Side) -> &'static str {
+    match side {
+        Side::Left => "left_paddle",
+        Side::Right => "right_paddle",
+    }
+}
+
+fn side_name(side: Side) -> &'static str {
+    match side {
+        Side::Left => "left",
+        Side::Right => "right",
+    }
+}
+
+fn free_side(assignments: &ControllerAssignments) -> Option<Side> {
+    if assignments.left.is_none() {
+        Some(Side::Left)
+    } else if assignments.right.is_none() {
+        Some(Side::Right)
+    } else {
+        None
+    }
+}
+
+/// Binds the paddle axis on `side` to its keyboard bindings and the given controller.
+fn assign(
+    input: &mut InputHandler<StringBindings>,
+    assignments: &mut ControllerAssignments,
+    side: Side,
+    controller_id: Option<u32>,
+) {
+    *assignments.slot_mut(side) = controller_id;
+
+    let axis_name = paddle_axis_name(side);
+    let mut axes = match input.bindings.axis(axis_name).cloned() {
+        Some(axis) => split_controllers(axis).0,
+        None => Vec::new(),
+    };
+
+    if let Some(controller_id) = controller_id {
+        let controller_axis = match assignments.template_mut(side).clone() {
+            Some(Axis::Controller {
+                axis,
+                invert,
+                dead_zone,
+                ..
+            }) => Axis::Controller {
+                controller_id,
+                axis,
+                invert,
+                dead_zone,
+            },
+            _ => Axis::Controller {
+                controller_id,
+                axis: ControllerAxis::LeftY,
+                invert: false,
+                dead_zone: DEFAULT_DEAD_ZONE,
+            },
+        };
+        axes.push(controller_axis);
+    }
+
+    let _ = input.bindings.remove_axis(axis_name);
+    if let Err(e) = input.bindings.insert_axis(axis_name, join_axes(axes)) {
+        log::error!("Failed to bind `{}`: {}", axis_name, e);
+    }
+}
+
+/// Returns the axes as one, `Multiple` unless there is only one.
+fn join_axes(mut axes: Vec<Axis>) -> Axis {
+    if axes.len() == 1 {
+        axes.remove(0)
+    } else {
+        Axis::Multiple(axes)
+    }
+}
+
+/// Splits an axis into its non-controller parts, and the first controller binding found.
+fn split_controllers(axis: Axis) -> (Vec<Axis>, Option<Axis>) {
+    match axis {
+        Axis::Controller { .. } => (Vec::new(), Some(axis)),
+        Axis::Multiple(axes) => {
+            axes.into_iter()
+                .fold((Vec::new(), None), |(mut others, template), axis| {
+                    let (mut axis_others, axis_template) = split_controllers(axis);
+                    others.append(&mut axis_others);
+                    (others, template.or(axis_template))
+                })
+        }
+        axis => (vec![axis], None),
+    }
+}
@@ -0,0 +1,90 @@
+use crate::{
+    event::PongEvent,
+    event_log::{BallSnapshot, EventLog, EventRecord},
+    frame_time::FrameTime,
+    Ball, Paddle, Side,
+};
+use amethyst::{
+    core::transform::Transform,
+    ecs::prelude::{Join, Read, ReadStorage, System, SystemData, World},
+    shrev::{EventChannel, ReaderId},
+};
+
+/// This system logs each event with the time, where the ball and paddles were, and who scored,
+/// when an `EventLog` is enabled.
+#[derive(Default)]
+pub struct EventLogSystem {
+    pong_event_reader: Option<ReaderId<PongEvent>>,
+}
+
+impl<'s> System<'s> for EventLogSystem {
+    type SystemData = (
+        Option<Read<'s, EventLog>>,
+        Read<'s, EventChannel<PongEvent>>,
+        Read<'s, FrameTime>,
+        ReadStorage<'s, Ball>,
+        ReadStorage<'s, Paddle>,
+        ReadStorage<'s, Transform>,
+    );
+
+    fn run(
+        &mut self,
+        (event_log, pong_events, time, balls, paddles, transforms): Self::SystemData,
+    ) {
+        let reader = self
+            .pong_event_reader
+            .as_mut()
+            .expect("EventLogSystem::setup has not been called");
+        // The events are read either way, so that they do not pile up.
+        let events = pong_events.read(reader);
+        let event_log = match event_log {
+            Some(event_log) if event_log.is_enabled() => event_log,
+            _ => return,
+        };
+
+        let ball = (&balls, &transforms)
+            .join()
+            .next()
+            .map(|(ball, transform)| BallSnapshot {
+                position: [transform.translation().x, transform.translation().y],
+                velocity: ball.velocity,
+            });
+        let (mut left_paddle, mut right_paddle) = (None, None);
+        for (paddle, transform) in (&paddles, &transforms).join() {
+            match paddle.side {
+                Side::Left => left_paddle = Some(transform.translation().y),
+                Side::Right => right_paddle = Some(transform.translation().y),
+            }
+        }
+
+        for event in events {
+            // Events about the ball carry where it was, such as before it is put back in the
+            // centre after a goal.
+            let ball = match event.ball_motion() {
+                Some((position, velocity)) => Some(BallSnapshot { position, velocity }),
+                None => ball.clone(),
+            };
+            event_log.push(EventRecord {
+                frame: event.frame(),
+                time: time.elapsed_seconds(),
+                event: *event,
+                ball,
+                left_paddle,
+                right_paddle,
+                scorer: match *event {
+                    PongEvent::Score { side, .. } => Some(side),
+                    _ => None,
+                },
+            });
+        }
+    }
+
+    fn setup(&mut self, world: &mut World) {
+        Self::SystemData::setup(world);
+        self.pong_event_reader = Some(
+            world
+                .fetch_mut::<EventChannel<PongEvent>>()
+                .register_reader(),
+        );
+    }
+}
@@ -0,0 +1,27 @@
+use crate::{
+    frame_time::{FrameTime, HEADLESS_FRAME_SECONDS},
+    session::Session,
+};
+use amethyst::{
+    core::timing::Time,
+    derive::SystemDesc,
+    ecs::prelude::{Read, System, SystemData, Write},
+};
+
+/// This system moves the `FrameTime` on from `Time`, before any system that moves the game.
+#[derive(SystemDesc)]
+pub struct FrameTimeSystem;
+
+impl<'s> System<'s> for FrameTimeSystem {
+    type SystemData = (Read<'s, Time>, Read<'s, Session>, Write<'s, FrameTime>);
+
+    fn run(&mut self, (time, session, mut frame_time): Self::SystemData) {
+        // Headless runs step at a fixed rate, as fast as they can.
+        let seconds = if session.headless {
+            HEADLESS_FRAME_SECONDS
+        } else {
+            time.delta_real_seconds()
+        };
+        frame_time.advance(seconds, time.time_scale());
+    }
+}
@@ -0,0 +1,160 @@
+use amethyst::{
+    ecs::{System, Write},
+    input::{
+        ControllerAxis, ControllerButton, ControllerEvent, InputEvent, InputHandler, StringBindings,
+    },
+    shrev::EventChannel,
+};
+use std::collections::HashMap;
+use wasm_bindgen::JsCast;
+use web_sys::{Gamepad, GamepadButton};
+
+/// Axes of the standard gamepad layout, by index in `Gamepad.axes`.
+const STANDARD_AXES: [ControllerAxis; 4] = [
+    ControllerAxis::LeftX,
+    ControllerAxis::LeftY,
+    ControllerAxis::RightX,
+    ControllerAxis::RightY,
+];
+
+/// Buttons of the standard gamepad layout, by index in `Gamepad.buttons`. The triggers at 6 and 7
+/// are read as axes, as with SDL.
+const STANDARD_BUTTONS: [Option<ControllerButton>; 17] = [
+    Some(ControllerButton::A),
+    Some(ControllerButton::B),
+    Some(ControllerButton::X),
+    Some(ControllerButton::Y),
+    Some(ControllerButton::LeftShoulder),
+    Some(ControllerButton::RightShoulder),
+    None,
+    None,
+    Some(ControllerButton::Back),
+    Some(ControllerButton::Start),
+    Some(ControllerButton::LeftStick),
+    Some(ControllerButton::RightStick),
+    Some(ControllerButton::DPadUp),
+    Some(ControllerButton::DPadDown),
+    Some(ControllerButton::DPadLeft),
+    Some(ControllerButton::DPadRight),
+    Some(ControllerButton::Guide),
+];
+
+/// Buttons of the standard gamepad layout read as trigger axes.
+const STANDARD_TRIGGERS: [(usize, ControllerAxis); 2] = [
+    (6, ControllerAxis::LeftTrigger),
+    (7, ControllerAxis::RightTrigger),
+];
+
+/// State of a gamepad as of the last poll.
+#[derive(Default)]
+struct GamepadState {
+    axes: HashMap<ControllerAxis, f32>,
+    pressed: Vec<bool>,
+}
+
+/// This system polls the browser's Gamepad API and feeds the gamepads to the `InputHandler`, in
+/// place of SDL on the wasm build. Gamepads are identified by their index in
+/// `navigator.getGamepads()`. It must run as a thread local system.
+#[derive(Default)]
+pub struct WebGamepadSystem {
+    gamepads: HashMap<u32, GamepadState>,
+}
+
+impl<'s> System<'s> for WebGamepadSystem {
+    type SystemData = (
+        Write<'s, InputHandler<StringBindings>>,
+        Write<'s, EventChannel<InputEvent<StringBindings>>>,
+    );
+
+    fn run(&mut self, (mut input, mut input_events): Self::SystemData) {
+        let mut send =
+            |event: ControllerEvent| input.send_controller_event(&event, &mut input_events);
+
+        let connected = connected_gamepads();
+        let disconnected = self
+            .gamepads
+            .keys()
+            .copied()
+            .filter(|which| !connected.iter().any(|gamepad| gamepad.index() == *which))
+            .collect::<Vec<_>>();
+        for which in disconnected {
+            self.gamepads.remove(&which);
+            send(ControllerEvent::ControllerDisconnected { which });
+        }
+
+        for gamepad in connected {
+            let which = gamepad.index();
+            let state = self.gamepads.entry(which).or_insert_with(|| {
+                send(ControllerEvent::ControllerConnected { which });
+                GamepadState::default()
+            });
+
+            // Without the standard mapping, the meaning of each axis and button is unknown.
+            if gamepad.mapping() != web_sys::GamepadMappingType::Standard {
+                continue;
+            }
+
+            let axes = gamepad.axes();
+            let buttons = gamepad.buttons();
+            let button = |index: usize| buttons.get(index as u32).dyn_into::<GamepadButton>().ok();
+
+            let axis_values = STANDARD_AXES
+                .iter()
+                .enumerate()
+                .map(|(index, axis)| (*axis, axes.get(index as u32).as_f64()))
+                .chain(
+                    STANDARD_TRIGGERS
+                        .iter()
+                        .map(|(index, axis)| (*axis, button(*index).map(|button| button.value()))),
+                );
+            for (axis, value) in axis_values {
+                let value = match value {
+                    Some(value) => value as f32,
+                    None => continue,
+                };
+                if state.axes.get(&axis) != Some(&value) {
+                    state.axes.insert(axis, value);
+                    send(ControllerEvent::ControllerAxisMoved { which, axis, value });
+                }
+            }
+
+            state.pressed.resize(STANDARD_BUTTONS.len(), false);
+            for (index, controller_button) in STANDARD_BUTTONS.iter().enumerate() {
+                let controller_button = match controller_button {
+                    Some(controller_button) => *controller_button,
+                    None => continue,
+                };
+                let pressed = button(index).map_or(false, |button| button.pressed());
+                if pressed != state.pressed[index] {
+                    state.pressed[index] = pressed;
+                    send(if pressed {
+                        ControllerEvent::ControllerButtonPressed {
+                            which,
+                            button: controller_button,
+                        }
+                    } else {
+                        ControllerEvent::ControllerButtonReleased {
+                            which,
+                            button: controller_button,
+                        }
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// Returns the gamepads the browser reports as connected.
+///
+/// Browsers only report gamepads once a button has been pressed on the page.
+fn connected_gamepads() -> Vec<Gamepad> {
+    let gamepads = match web_sys::window().map(|window| window.navigator().get_gamepads()) {
+        Some(Ok(gamepads)) => gamepads,
+        _ => return Vec::new(),
+    };
+    gamepads
+        .iter()
+        .filter_map(|gamepad| gamepad.dyn_into::<Gamepad>().ok())
+        .filter(Gamepad::connected)
+        .collect()
+}
@@ -0,0 +1,81 @@
+use crate::{
+    event::PongEvent,
+    event_log::BallSnapshot,
+    host::{GameSnapshot, HostLink},
+    stats::MatchStats,
+    Ball, ScoreBoard,
+};
+use amethyst::{
+    core::{timing::Time, transform::Transform},
+    derive::SystemDesc,
+    ecs::prelude::{Join, Read, ReadStorage, System, SystemData, World},
+    shrev::{EventChannel, ReaderId},
+};
+
+/// This system publishes the state of the game for the embedding page to read.
+#[derive(SystemDesc)]
+pub struct HostSnapshotSystem;
+
+impl<'s> System<'s> for HostSnapshotSystem {
+    type SystemData = (
+        Option<Read<'s, HostLink>>,
+        Read<'s, ScoreBoard>,
+        Read<'s, MatchStats>,
+        Read<'s, Time>,
+        ReadStorage<'s, Ball>,
+        ReadStorage<'s, Transform>,
+    );
+
+    fn run(&mut self, (host_link, score_board, stats, time, balls, transforms): Self::SystemData) {
+        let host_link = match host_link {
+            Some(host_link) => host_link,
+            None => return,
+        };
+
+        let ball = (&balls, &transforms)
+            .join()
+            .next()
+            .map(|(ball, transform)| BallSnapshot {
+                position: [transform.translation().x, transform.translation().y],
+                velocity: ball.velocity,
+            });
+        host_link.set_snapshot(GameSnapshot {
+            score_left: score_board.score_left,
+            score_right: score_board.score_right,
+            winner: score_board.winner(),
+            paused: time.time_scale() == 0.0,
+            ball,
+            stats: stats.clone(),
+        });
+    }
+}
+
+/// This system forwards events to the callbacks registered by the embedding page. It must run as
+/// a thread local system, as the callbacks live on the main thread.
+#[derive(Default)]
+pub struct HostEventSystem {
+    pong_event_reader: Option<ReaderId<PongEvent>>,
+}
+
+impl<'s> System<'s> for HostEventSystem {
+    type SystemData = Read<'s, EventChannel<PongEvent>>;
+
+    fn run(&mut self, pong_events: Self::SystemData) {
+        let reader = self
+            .pong_event_reader
+            .as_mut()
+            .expect("HostEventSystem::setup has not been called");
+        pong_events
+            .read(reader)
+            .for_each(crate::host::dispatch_event);
+    }
+
+    fn setup(&mut self, world: &mut World) {
+        Self::SystemData::setup(world);
+        self.pong_event_reader = Some(
+            world
+                .fetch_mut::<EventChannel<PongEvent>>()
+                .register_reader(),
+        );
+    }
+}
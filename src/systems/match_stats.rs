@@ -0,0 +1,56 @@
+use crate::{
+    event::PongEvent, frame_time::FrameTime, game_config::GameConfig, stats::MatchStats, Ball,
+};
+use amethyst::{
+    core::transform::Transform,
+    ecs::prelude::{Join, Read, ReadStorage, System, SystemData, World, Write},
+    shrev::{EventChannel, ReaderId},
+};
+
+/// This system keeps the `MatchStats` up to date from the bounce, serve and score events, and
+/// from where the ball is each frame.
+#[derive(Default)]
+pub struct MatchStatsSystem {
+    pong_event_reader: Option<ReaderId<PongEvent>>,
+}
+
+impl<'s> System<'s> for MatchStatsSystem {
+    type SystemData = (
+        Read<'s, EventChannel<PongEvent>>,
+        Write<'s, MatchStats>,
+        Read<'s, GameConfig>,
+        Read<'s, FrameTime>,
+        ReadStorage<'s, Ball>,
+        ReadStorage<'s, Transform>,
+    );
+
+    fn run(
+        &mut self,
+        (pong_events, mut stats, game_config, time, balls, transforms): Self::SystemData,
+    ) {
+        let reader = self
+            .pong_event_reader
+            .as_mut()
+            .expect("MatchStatsSystem::setup has not been called");
+        for event in pong_events.read(reader) {
+            stats.record(event, game_config.arena.height);
+        }
+
+        for (_, transform) in (&balls, &transforms).join() {
+            stats.track_ball(
+                transform.translation().x,
+                game_config.arena.width,
+                time.delta_seconds(),
+            );
+        }
+    }
+
+    fn setup(&mut self, world: &mut World) {
+        Self::SystemData::setup(world);
+        self.pong_event_reader = Some(
+            world
+                .fetch_mut::<EventChannel<PongEvent>>()
+                .register_reader(),
+        );
+    }
+}
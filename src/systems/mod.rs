@@ -1,13 +1,62 @@
+#[cfg(not(feature = "wasm"))]
 mod audio;
+mod audio_settings;
 mod bounce;
+#[cfg(not(feature = "wasm"))]
+mod config_reload;
+mod controller;
+mod event_log;
+mod frame_time;
+#[cfg(feature = "wasm")]
+mod gamepad;
+#[cfg(feature = "wasm")]
+mod host;
+mod match_stats;
 mod move_balls;
+#[cfg(not(feature = "wasm"))]
+mod music;
 mod paddle;
+mod paddle_ai;
+mod paddle_input;
+mod paddle_pointer;
+mod record;
+mod score_ui;
+mod toast;
+mod touch;
+mod viewport;
+#[cfg(feature = "wasm")]
+mod web_audio;
 mod winner;
 
+#[cfg(not(feature = "wasm"))]
 pub use self::{
     audio::AudioSystem,
+    config_reload::{ConfigFiles, ConfigReloadSystem},
+    music::MusicDirectorSystem,
+};
+pub use self::{
+    audio_settings::AudioSettingsSystem,
     bounce::BounceSystem,
+    controller::{paddle_axis_name, ControllerAssignmentSystem, ControllerAssignments},
+    event_log::EventLogSystem,
+    frame_time::FrameTimeSystem,
+    match_stats::MatchStatsSystem,
     move_balls::MoveBallsSystem,
     paddle::PaddleSystem,
-    winner::{ScoreText, WinnerSystem},
+    paddle_ai::PaddleAiSystem,
+    paddle_input::PaddleInputSystem,
+    paddle_pointer::PaddlePointerSystem,
+    record::RecordSystem,
+    score_ui::{ScoreText, ScoreUiSystem},
+    toast::{ToastSystem, ToastText},
+    touch::TouchSystem,
+    viewport::ViewportSystem,
+    winner::WinnerSystem,
+};
+#[cfg(feature = "wasm")]
+pub use self::{
+    gamepad::WebGamepadSystem,
+    host::{HostEventSystem, HostSnapshotSystem},
+    viewport::CanvasResizeSystem,
+    web_audio::WebAudioSystem,
 };
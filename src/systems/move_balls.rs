@@ -1,6 +1,6 @@
-use crate::Ball;
+use crate::{frame_time::FrameTime, Ball};
 use amethyst::{
-    core::{timing::Time, transform::Transform},
+    core::transform::Transform,
     derive::SystemDesc,
     ecs::prelude::{Join, Read, ReadStorage, System, SystemData, WriteStorage},
 };
@@ -14,7 +14,7 @@ impl<'s> System<'s> for MoveBallsSystem {
     type SystemData = (
         ReadStorage<'s, Ball>,
         WriteStorage<'s, Transform>,
-        Read<'s, Time>,
+        Read<'s, FrameTime>,
     );
 
     fn run(&mut self, (balls, mut locals, time): Self::SystemData) {
@@ -0,0 +1,103 @@
+use crate::{
+    audio::{music_cue, AudioSettings, Music, MusicDirector, MusicTrack, TrackStart},
+    event::PongEvent,
+    ScoreBoard,
+};
+use amethyst::{
+    assets::AssetStorage,
+    audio::Source,
+    core::timing::Time,
+    ecs::{Read, System, SystemData, World, Write},
+    shrev::{EventChannel, ReaderId},
+};
+use rodio::{Decoder, Device, Sink, Source as _};
+use std::io::Cursor;
+
+impl MusicTrack for Sink {
+    fn set_volume(&mut self, volume: f32) {
+        Sink::set_volume(self, volume);
+    }
+
+    fn is_finished(&self) -> bool {
+        self.empty()
+    }
+}
+
+/// This system plays the background music. It follows the state of the match: intense tracks
+/// near match point, quieter music while paused and the game over tracks once a side has won.
+/// The music is ducked under score sounds, and changes of track are crossfaded.
+#[derive(Default)]
+pub struct MusicDirectorSystem {
+    pong_event_reader: Option<ReaderId<PongEvent>>,
+    device: Option<Device>,
+    director: MusicDirector<Sink>,
+}
+
+impl<'s> System<'s> for MusicDirectorSystem {
+    type SystemData = (
+        Read<'s, AssetStorage<Source>>,
+        Option<Write<'s, Music>>,
+        Read<'s, EventChannel<PongEvent>>,
+        Read<'s, AudioSettings>,
+        Read<'s, ScoreBoard>,
+        Read<'s, Time>,
+    );
+
+    fn run(
+        &mut self,
+        (storage, music, pong_events, audio_settings, score_board, time): Self::SystemData,
+    ) {
+        let reader = self
+            .pong_event_reader
+            .as_mut()
+            .expect("MusicDirectorSystem::setup has not been called");
+        if pong_events
+            .read(reader)
+            .any(|event| matches!(event, PongEvent::Score { .. }))
+        {
+            self.director.duck();
+        }
+
+        let (device, mut music) = match (self.device.as_ref(), music) {
+            (Some(device), Some(music)) => (device, music),
+            _ => return,
+        };
+
+        self.director.update(
+            &mut music,
+            music_cue(&score_board, &time),
+            audio_settings.music_volume(),
+            time.delta_real_seconds(),
+            |handle| match storage.get(handle) {
+                Some(source) => start_track(device, source),
+                None => TrackStart::Loading,
+            },
+        );
+    }
+
+    fn setup(&mut self, world: &mut World) {
+        Self::SystemData::setup(world);
+        self.pong_event_reader = Some(
+            world
+                .fetch_mut::<EventChannel<PongEvent>>()
+                .register_reader(),
+        );
+        self.device = rodio::default_output_device();
+    }
+}
+
+/// Starts playing a track on a new sink, silent until it fades in.
+fn start_track(device: &Device, source: &Source) -> TrackStart<Sink> {
+    let decoder = match Decoder::new(Cursor::new(source.clone())) {
+        Ok(decoder) => decoder,
+        Err(e) => {
+            log::error!("Failed to decode music track: {}", e);
+            return TrackStart::Failed;
+        }
+    };
+
+    let sink = Sink::new(device);
+    sink.set_volume(0.0);
+    sink.append(decoder.convert_samples::<f32>());
+    TrackStart::Playing(sink)
+}
@@ -1,8 +1,11 @@
-use crate::Paddle;
+use crate::{
+    frame_time::FrameTime, game_config::GameConfig, input_profile::PaddleAxisValues,
+    systems::paddle_axis_name, Paddle, PaddleControl,
+};
 use amethyst::{
-    core::{timing::Time, transform::Transform},
+    core::transform::Transform,
     derive::SystemDesc,
-    ecs::prelude::{Join, Read, ReadStorage, System, SystemData, WriteStorage},
+    ecs::prelude::{Join, Read, System, SystemData, WriteStorage},
     input::{InputHandler, StringBindings},
 };
 
@@ -13,35 +16,43 @@ pub struct PaddleSystem;
 
 impl<'s> System<'s> for PaddleSystem {
     type SystemData = (
-        ReadStorage<'s, Paddle>,
+        WriteStorage<'s, Paddle>,
         WriteStorage<'s, Transform>,
-        Read<'s, Time>,
+        Read<'s, FrameTime>,
         Read<'s, InputHandler<StringBindings>>,
+        Read<'s, PaddleAxisValues>,
+        Read<'s, GameConfig>,
     );
 
-    fn run(&mut self, (paddles, mut transforms, time, input): Self::SystemData) {
-        use crate::Side;
-
+    fn run(
+        &mut self,
+        (mut paddles, mut transforms, time, input, axis_values, game_config): Self::SystemData,
+    ) {
         // Iterate over all planks and move them according to the input the user
         // provided.
-        for (paddle, transform) in (&paddles, &mut transforms).join() {
-            let opt_movement = match paddle.side {
-                Side::Left => input.axis_value("left_paddle"),
-                Side::Right => input.axis_value("right_paddle"),
-            };
+        for (paddle, transform) in (&mut paddles, &mut transforms).join() {
+            match paddle.control {
+                PaddleControl::Axis => {}
+                // Pressing the keys takes the paddle back from the pointer.
+                PaddleControl::Pointer => match input.axis_value(paddle_axis_name(paddle.side)) {
+                    Some(raw) if raw != 0.0 => paddle.control = PaddleControl::Axis,
+                    _ => continue,
+                },
+                PaddleControl::Ai | PaddleControl::Replay => continue,
+            }
+
+            // The axis value has been shaped by the paddle's input profile.
+            let opt_movement = axis_values.get(paddle.side);
 
             if let Some(movement) = opt_movement {
-                use crate::ARENA_HEIGHT;
-                transform.prepend_translation_y(
-                    paddle.velocity * time.delta_seconds() * movement as f32,
-                );
+                transform.prepend_translation_y(paddle.velocity * time.delta_seconds() * movement);
 
                 // We make sure the paddle remains in the arena.
                 let paddle_y = transform.translation().y;
                 transform.set_translation_y(
                     paddle_y
                         .max(paddle.height * 0.5)
-                        .min(ARENA_HEIGHT - paddle.height * 0.5),
+                        .min(game_config.arena.height - paddle.height * 0.5),
                 );
             }
         }
@@ -0,0 +1,102 @@
+use crate::{
+    frame_time::FrameTime,
+    game_config::{GameConfig, GameRng},
+    Ball, Paddle, PaddleControl, Side,
+};
+use amethyst::{
+    core::transform::Transform,
+    ecs::prelude::{Join, Read, ReadStorage, System, SystemData, Write, WriteStorage},
+};
+use rand::Rng;
+use std::collections::HashMap;
+
+/// Seconds the computer takes to react to the ball turning towards its paddle.
+const REACTION_SECONDS: f32 = 0.25;
+/// Largest distance the computer aims off the ball by, as a share of the paddle height. The
+/// paddle misses the ball beyond half its height plus the radius of the ball.
+const AIM_ERROR: f32 = 0.75;
+
+/// How the computer plays the ball coming towards one of its paddles.
+struct Aim {
+    /// Seconds left before the paddle starts following the ball.
+    reaction: f32,
+    /// Distance from the ball the paddle aims its middle at.
+    offset: f32,
+}
+
+/// This system is responsible for moving the paddles driven by the computer.
+///
+/// A paddle follows the ball coming towards it, and drifts back to the middle otherwise, no
+/// faster than a player could move it. Like a player, it reacts late and aims a little off,
+/// sometimes far enough to miss.
+#[derive(Default)]
+pub struct PaddleAiSystem {
+    aims: HashMap<Side, Aim>,
+}
+
+impl<'s> System<'s> for PaddleAiSystem {
+    type SystemData = (
+        ReadStorage<'s, Paddle>,
+        ReadStorage<'s, Ball>,
+        WriteStorage<'s, Transform>,
+        Read<'s, FrameTime>,
+        Read<'s, GameConfig>,
+        Write<'s, GameRng>,
+    );
+
+    fn run(
+        &mut self,
+        (paddles, balls, mut transforms, time, game_config, mut rng): Self::SystemData,
+    ) {
+        let ball_positions = (&balls, &transforms)
+            .join()
+            .map(|(ball, transform)| (ball.velocity[0], transform.translation().y))
+            .collect::<Vec<_>>();
+
+        for (paddle, transform) in (&paddles, &mut transforms).join() {
+            if paddle.control != PaddleControl::Ai {
+                continue;
+            }
+
+            let incoming = |velocity_x: f32| match paddle.side {
+                Side::Left => velocity_x < 0.0,
+                Side::Right => velocity_x > 0.0,
+            };
+            let ball_y = ball_positions
+                .iter()
+                .find(|(velocity_x, _)| incoming(*velocity_x))
+                .map(|(_, y)| *y);
+            let target = match ball_y {
+                Some(ball_y) => {
+                    let aim = self.aims.entry(paddle.side).or_insert_with(|| {
+                        let error = paddle.height * AIM_ERROR;
+                        Aim {
+                            reaction: REACTION_SECONDS,
+                            offset: rng.0.gen_range(-error, error),
+                        }
+                    });
+                    aim.reaction -= time.delta_seconds();
+                    if aim.reaction > 0.0 {
+                        continue;
+                    }
+                    ball_y + aim.offset
+                }
+                None => {
+                    self.aims.remove(&paddle.side);
+                    game_config.arena.height * 0.5
+                }
+            };
+
+            let max_step = paddle.velocity * time.delta_seconds();
+            let paddle_y = transform.translation().y;
+            let step = (target - paddle_y).max(-max_step).min(max_step);
+
+            // We make sure the paddle remains in the arena.
+            transform.set_translation_y(
+                (paddle_y + step)
+                    .max(paddle.height * 0.5)
+                    .min(game_config.arena.height - paddle.height * 0.5),
+            );
+        }
+    }
+}
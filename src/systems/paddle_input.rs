@@ -0,0 +1,38 @@
+use crate::{
+    frame_time::FrameTime,
+    input_profile::{InputProfiles, PaddleAxisValues},
+    systems::paddle_axis_name,
+    Side,
+};
+use amethyst::{
+    derive::SystemDesc,
+    ecs::prelude::{Read, System, SystemData, Write},
+    input::{InputHandler, StringBindings},
+};
+
+/// This system is responsible for shaping the raw paddle axis values with each paddle's input
+/// profile.
+#[derive(SystemDesc)]
+pub struct PaddleInputSystem;
+
+impl<'s> System<'s> for PaddleInputSystem {
+    type SystemData = (
+        Read<'s, InputHandler<StringBindings>>,
+        Read<'s, InputProfiles>,
+        Read<'s, FrameTime>,
+        Write<'s, PaddleAxisValues>,
+    );
+
+    fn run(&mut self, (input, profiles, time, mut axis_values): Self::SystemData) {
+        for &side in &[Side::Left, Side::Right] {
+            let profile = profiles.get(side);
+            let value = axis_values.get_mut(side);
+            let previous = value.unwrap_or(0.0);
+
+            *value = input.axis_value(paddle_axis_name(side)).map(|raw| {
+                let target = profile.response(raw as f32);
+                profile.smooth(previous, target, time.delta_seconds())
+            });
+        }
+    }
+}
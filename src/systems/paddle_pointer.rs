@@ -0,0 +1,117 @@
+use crate::{
+    frame_time::FrameTime, game_config::GameConfig, pointer::TouchInput, Paddle, PaddleControl,
+    Side,
+};
+use amethyst::{
+    core::{
+        math::{Point3, Vector2},
+        transform::Transform,
+    },
+    derive::SystemDesc,
+    ecs::prelude::{Join, Read, ReadExpect, ReadStorage, System, SystemData, WriteStorage},
+    input::{InputHandler, StringBindings},
+    renderer::Camera,
+    window::ScreenDimensions,
+    winit::event::MouseButton,
+};
+
+/// This system is responsible for moving paddles towards the mouse cursor or a touch point.
+///
+/// Clicking on one half of the screen hands the mouse to the paddle on that side. Touches on
+/// either half always drive the paddle on that side.
+#[derive(SystemDesc)]
+pub struct PaddlePointerSystem;
+
+impl<'s> System<'s> for PaddlePointerSystem {
+    type SystemData = (
+        WriteStorage<'s, Paddle>,
+        WriteStorage<'s, Transform>,
+        ReadStorage<'s, Camera>,
+        Read<'s, FrameTime>,
+        Read<'s, InputHandler<StringBindings>>,
+        Read<'s, TouchInput>,
+        ReadExpect<'s, ScreenDimensions>,
+        Read<'s, GameConfig>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            mut paddles,
+            mut transforms,
+            cameras,
+            time,
+            input,
+            touch_input,
+            screen_dimensions,
+            game_config,
+        ): Self::SystemData,
+    ) {
+        let (camera, camera_transform) = match (&cameras, &transforms).join().next() {
+            Some((camera, transform)) => (camera.clone(), transform.clone()),
+            None => return,
+        };
+        let screen_diagonal = Vector2::new(screen_dimensions.width(), screen_dimensions.height());
+        let side_of = |x: f32| {
+            if x < screen_dimensions.width() * 0.5 {
+                Side::Left
+            } else {
+                Side::Right
+            }
+        };
+        let arena_y = |(x, y): (f32, f32)| {
+            camera
+                .projection()
+                .screen_to_world_point(Point3::new(x, y, 0.0), screen_diagonal, &camera_transform)
+                .y
+        };
+
+        let mouse_position = input.mouse_position();
+        if let (Some((x, _)), true) = (
+            mouse_position,
+            input.mouse_button_is_down(MouseButton::Left),
+        ) {
+            // Hand the mouse over to the paddle on the clicked side. Replayed paddles are left
+            // alone.
+            let clicked_side = side_of(x);
+            for paddle in (&mut paddles).join() {
+                if paddle.control == PaddleControl::Replay {
+                    continue;
+                } else if paddle.side == clicked_side {
+                    paddle.control = PaddleControl::Pointer;
+                } else if paddle.control == PaddleControl::Pointer {
+                    paddle.control = PaddleControl::Axis;
+                }
+            }
+        }
+
+        let touches = touch_input.positions();
+        for (paddle, transform) in (&paddles, &mut transforms).join() {
+            let touch_position = touches
+                .iter()
+                .copied()
+                .find(|&(x, _)| side_of(x) == paddle.side);
+            let target = match (touch_position, paddle.control) {
+                (_, PaddleControl::Replay) => continue,
+                (Some(position), _) => position,
+                (None, PaddleControl::Pointer) => match mouse_position {
+                    Some(position) => position,
+                    None => continue,
+                },
+                (None, PaddleControl::Axis) | (None, PaddleControl::Ai) => continue,
+            };
+
+            // Follow the pointer, but no faster than the paddle is allowed to move.
+            let max_step = paddle.velocity * time.delta_seconds();
+            let paddle_y = transform.translation().y;
+            let step = (arena_y(target) - paddle_y).max(-max_step).min(max_step);
+
+            // We make sure the paddle remains in the arena.
+            transform.set_translation_y(
+                (paddle_y + step)
+                    .max(paddle.height * 0.5)
+                    .min(game_config.arena.height - paddle.height * 0.5),
+            );
+        }
+    }
+}
@@ -0,0 +1,50 @@
+use crate::{
+    frame_time::FrameTime,
+    session::{RecordedFrame, Recording},
+    Paddle, Side,
+};
+use amethyst::{
+    core::transform::Transform,
+    derive::SystemDesc,
+    ecs::prelude::{Join, Read, ReadStorage, System, SystemData, Write},
+};
+
+/// This system records the length of each frame and where the paddles end up, while a
+/// `Recording` is in the world.
+#[derive(SystemDesc)]
+pub struct RecordSystem;
+
+impl<'s> System<'s> for RecordSystem {
+    type SystemData = (
+        Option<Write<'s, Recording>>,
+        ReadStorage<'s, Paddle>,
+        ReadStorage<'s, Transform>,
+        Read<'s, FrameTime>,
+    );
+
+    fn run(&mut self, (recording, paddles, transforms, time): Self::SystemData) {
+        let mut recording = match recording {
+            Some(recording) => recording,
+            None => return,
+        };
+        // Frames where nothing moves, such as while paused, are left out.
+        if time.delta_seconds() == 0.0 {
+            return;
+        }
+
+        let (mut left, mut right) = (None, None);
+        for (paddle, transform) in (&paddles, &transforms).join() {
+            match paddle.side {
+                Side::Left => left = Some(transform.translation().y),
+                Side::Right => right = Some(transform.translation().y),
+            }
+        }
+        if let (Some(left), Some(right)) = (left, right) {
+            recording.frames.push(RecordedFrame {
+                delta: time.delta_seconds(),
+                left,
+                right,
+            });
+        }
+    }
+}
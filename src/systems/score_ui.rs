@@ -0,0 +1,133 @@
+use crate::{
+    event::PongEvent,
+    game_config::{GameConfig, ScoreAnimation},
+    viewport::ArenaUi,
+    ScoreBoard, Side,
+};
+use amethyst::{
+    core::timing::Time,
+    ecs::prelude::{Entity, Read, System, SystemData, World, WriteStorage},
+    shrev::{EventChannel, ReaderId},
+    ui::UiText,
+};
+
+/// Length of the animation of a score that went up, in seconds.
+const ANIMATION_SECONDS: f32 = 0.4;
+/// Growth of a popping score at the start of its animation.
+const POP_SCALE: f32 = 0.5;
+const SCORE_COLOUR: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+const FLASH_COLOUR: [f32; 4] = [1.0, 0.85, 0.3, 1.0];
+
+/// This system shows the scores of the `ScoreBoard`, and animates a score when it goes up. It is
+/// left out of headless runs.
+#[derive(Default)]
+pub struct ScoreUiSystem {
+    pong_event_reader: Option<ReaderId<PongEvent>>,
+    /// Scores last shown, `None` before they are first shown.
+    shown: Option<(i32, i32)>,
+    /// Seconds left of the animation of the left and right scores.
+    animations: [f32; 2],
+}
+
+impl<'s> System<'s> for ScoreUiSystem {
+    type SystemData = (
+        Read<'s, EventChannel<PongEvent>>,
+        Option<Read<'s, ScoreText>>,
+        Read<'s, ScoreBoard>,
+        Read<'s, GameConfig>,
+        Read<'s, Time>,
+        WriteStorage<'s, UiText>,
+        WriteStorage<'s, ArenaUi>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (pong_events, score_text, score_board, game_config, time, mut texts, mut arena_uis) =
+            data;
+        let reader = self
+            .pong_event_reader
+            .as_mut()
+            .expect("ScoreUiSystem::setup has not been called");
+        let events = pong_events.read(reader);
+        // There is nothing to show before the game starts.
+        let score_text = match score_text {
+            Some(score_text) => score_text,
+            None => return,
+        };
+
+        for event in events {
+            if let PongEvent::Score { side, .. } = *event {
+                self.animations[side_index(side)] = ANIMATION_SECONDS;
+            }
+        }
+
+        // The scores are read from the board, which also changes when a match is restored or
+        // started afresh.
+        let scores = (score_board.score_left, score_board.score_right);
+        if self.shown != Some(scores) {
+            self.shown = Some(scores);
+            for (entity, score) in &[
+                (score_text.p1_score, scores.0),
+                (score_text.p2_score, scores.1),
+            ] {
+                if let Some(text) = texts.get_mut(*entity) {
+                    text.text = score.to_string();
+                }
+            }
+        }
+
+        // Animations run in real time, so that they finish while the game is over or paused.
+        let config = &game_config.score_ui;
+        for (remaining, entity) in self
+            .animations
+            .iter_mut()
+            .zip(&[score_text.p1_score, score_text.p2_score])
+        {
+            if *remaining <= 0.0 {
+                continue;
+            }
+            *remaining = (*remaining - time.delta_real_seconds()).max(0.0);
+            let progress = *remaining / ANIMATION_SECONDS;
+            // Both the size and the colour are set, so that a config reload switching the
+            // animation midway leaves neither in its animated state.
+            let (pop, flash) = match config.animation {
+                ScoreAnimation::Off => (0.0, 0.0),
+                ScoreAnimation::Pop => (progress, 0.0),
+                ScoreAnimation::Flash => (0.0, progress),
+            };
+            // The text is laid out again with its new size by the `ViewportSystem`.
+            if let Some(arena_ui) = arena_uis.get_mut(*entity) {
+                arena_ui.font_size = config.font_size * (1.0 + POP_SCALE * pop);
+                arena_ui.laid_out_for = None;
+            }
+            if let Some(text) = texts.get_mut(*entity) {
+                for ((colour, score), flash_colour) in
+                    text.color.iter_mut().zip(&SCORE_COLOUR).zip(&FLASH_COLOUR)
+                {
+                    *colour = score + (flash_colour - score) * flash;
+                }
+            }
+        }
+    }
+
+    fn setup(&mut self, world: &mut World) {
+        Self::SystemData::setup(world);
+        self.pong_event_reader = Some(
+            world
+                .fetch_mut::<EventChannel<PongEvent>>()
+                .register_reader(),
+        );
+    }
+}
+
+fn side_index(side: Side) -> usize {
+    match side {
+        Side::Left => 0,
+        Side::Right => 1,
+    }
+}
+
+/// Stores the entities that are displaying the player score with UiText.
+pub struct ScoreText {
+    pub p1_score: Entity,
+    pub p2_score: Entity,
+}
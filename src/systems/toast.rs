@@ -0,0 +1,50 @@
+use crate::toast::{Toasts, TOAST_DURATION};
+use amethyst::{
+    core::timing::Time,
+    ecs::prelude::{Entity, Read, System, SystemData, Write, WriteStorage},
+    ui::UiText,
+};
+
+/// This system is responsible for showing queued toasts one after the other.
+#[derive(Default)]
+pub struct ToastSystem {
+    /// Seconds left before the current toast is hidden.
+    remaining: f32,
+}
+
+impl<'s> System<'s> for ToastSystem {
+    type SystemData = (
+        WriteStorage<'s, UiText>,
+        Write<'s, Toasts>,
+        Read<'s, Time>,
+        Option<Read<'s, ToastText>>,
+    );
+
+    fn run(&mut self, (mut text, mut toasts, time, toast_text): Self::SystemData) {
+        // Toasts wait until the game has started.
+        let toast_text = match toast_text {
+            Some(toast_text) => toast_text,
+            None => return,
+        };
+
+        self.remaining -= time.delta_seconds();
+        if self.remaining > 0.0 {
+            return;
+        }
+
+        if let Some(text) = text.get_mut(toast_text.entity) {
+            match toasts.pop() {
+                Some(message) => {
+                    text.text = message;
+                    self.remaining = TOAST_DURATION;
+                }
+                None => text.text.clear(),
+            }
+        }
+    }
+}
+
+/// Stores the entity that displays toasts with UiText.
+pub struct ToastText {
+    pub entity: Entity,
+}
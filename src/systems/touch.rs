@@ -0,0 +1,49 @@
+use crate::pointer::TouchInput;
+use amethyst::{
+    ecs::{Read, System, SystemData, World},
+    shrev::{EventChannel, ReaderId},
+    winit::event::{Event, TouchPhase, WindowEvent},
+};
+
+/// This system is responsible for recording the touch points reported by the window.
+#[derive(Default)]
+pub struct TouchSystem {
+    event_reader: Option<ReaderId<Event<'static, ()>>>,
+}
+
+impl<'s> System<'s> for TouchSystem {
+    type SystemData = (
+        Read<'s, EventChannel<Event<'static, ()>>>,
+        Read<'s, TouchInput>,
+    );
+
+    fn run(&mut self, (events, touch_input): Self::SystemData) {
+        let reader = self
+            .event_reader
+            .as_mut()
+            .expect("TouchSystem::setup has not been called");
+
+        events.read(reader).for_each(|event| {
+            if let Event::WindowEvent {
+                event: WindowEvent::Touch(touch),
+                ..
+            } = event
+            {
+                match touch.phase {
+                    TouchPhase::Started | TouchPhase::Moved => touch_input
+                        .touch_moved(touch.id, (touch.location.x as f32, touch.location.y as f32)),
+                    TouchPhase::Ended | TouchPhase::Cancelled => touch_input.touch_ended(touch.id),
+                }
+            }
+        });
+    }
+
+    fn setup(&mut self, world: &mut World) {
+        Self::SystemData::setup(world);
+        self.event_reader = Some(
+            world
+                .fetch_mut::<EventChannel<Event<'static, ()>>>()
+                .register_reader(),
+        );
+    }
+}
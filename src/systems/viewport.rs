@@ -0,0 +1,136 @@
+use crate::{
+    game_config::GameConfig,
+    viewport::{anchor_offset, ArenaUi, Letterbox, Viewport},
+};
+use amethyst::{
+    ecs::prelude::{Join, Read, ReadExpect, System, WriteStorage},
+    renderer::Camera,
+    ui::{Anchor, UiText, UiTransform},
+    window::ScreenDimensions,
+};
+
+/// This system fits the arena into the window as it resizes, keeping its aspect ratio.
+///
+/// The camera is widened to cover the whole window, the space around the arena is covered by the
+/// letterbox bars, and the UI laid out relative to the arena is moved and scaled with it.
+#[derive(Default)]
+pub struct ViewportSystem {
+    /// Screen size the camera and bars were last fitted to.
+    fitted_to: Option<(f32, f32)>,
+}
+
+impl<'s> System<'s> for ViewportSystem {
+    type SystemData = (
+        ReadExpect<'s, ScreenDimensions>,
+        Read<'s, GameConfig>,
+        Option<Read<'s, Letterbox>>,
+        WriteStorage<'s, Camera>,
+        WriteStorage<'s, UiTransform>,
+        WriteStorage<'s, UiText>,
+        WriteStorage<'s, ArenaUi>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            screen_dimensions,
+            game_config,
+            letterbox,
+            mut cameras,
+            mut ui_transforms,
+            mut ui_texts,
+            mut arena_uis,
+        ): Self::SystemData,
+    ) {
+        let screen = (screen_dimensions.width(), screen_dimensions.height());
+        if screen.0 <= 0.0 || screen.1 <= 0.0 {
+            // The window is minimised.
+            return;
+        }
+        let viewport = Viewport::fit(screen.0, screen.1, &game_config.arena);
+
+        if self.fitted_to != Some(screen) {
+            let mut fitted = false;
+            for camera in (&mut cameras).join() {
+                *camera = Camera::standard_2d(screen.0 / viewport.scale, screen.1 / viewport.scale);
+                fitted = true;
+            }
+            if let Some(letterbox) = letterbox {
+                for (index, &bar) in letterbox.bars.iter().enumerate() {
+                    if let Some(transform) = ui_transforms.get_mut(bar) {
+                        place_bar(transform, index, screen, &viewport);
+                    }
+                }
+            }
+            // Try again next frame if the camera has not been created yet.
+            if fitted {
+                self.fitted_to = Some(screen);
+            }
+        }
+
+        let ui_scale = viewport.ui_scale();
+        for (arena_ui, transform, text) in
+            (&mut arena_uis, &mut ui_transforms, (&mut ui_texts).maybe()).join()
+        {
+            if arena_ui.laid_out_for == Some(screen) {
+                continue;
+            }
+            let (offset_x, offset_y) = anchor_offset(&transform.anchor, &viewport);
+            transform.local_x = arena_ui.x * ui_scale + offset_x;
+            transform.local_y = arena_ui.y * ui_scale + offset_y;
+            transform.width = arena_ui.width * ui_scale;
+            transform.height = arena_ui.height * ui_scale;
+            if let Some(text) = text {
+                text.font_size = arena_ui.font_size * ui_scale;
+            }
+            arena_ui.laid_out_for = Some(screen);
+        }
+    }
+}
+
+/// Places one of the two bars on either side of the arena.
+fn place_bar(transform: &mut UiTransform, index: usize, screen: (f32, f32), viewport: &Viewport) {
+    let (anchor, width, height) = if viewport.offset_x > 0.0 {
+        let anchor = if index == 0 {
+            Anchor::MiddleLeft
+        } else {
+            Anchor::MiddleRight
+        };
+        (anchor, viewport.offset_x, screen.1)
+    } else {
+        let anchor = if index == 0 {
+            Anchor::TopMiddle
+        } else {
+            Anchor::BottomMiddle
+        };
+        (anchor, screen.0, viewport.offset_y)
+    };
+    transform.anchor = anchor;
+    transform.pivot = anchor;
+    transform.local_x = 0.0;
+    transform.local_y = 0.0;
+    transform.width = width;
+    transform.height = height;
+}
+
+/// This system resizes the canvas to the size requested by the page. It must run as a thread
+/// local system, as the window lives on the main thread.
+#[cfg(feature = "wasm")]
+#[derive(Default)]
+pub struct CanvasResizeSystem;
+
+#[cfg(feature = "wasm")]
+impl<'s> System<'s> for CanvasResizeSystem {
+    type SystemData = (
+        Read<'s, crate::viewport::CanvasSize>,
+        ReadExpect<'s, amethyst::window::Window>,
+    );
+
+    fn run(&mut self, (canvas_size, window): Self::SystemData) {
+        // The canvas is sized in CSS pixels, winit scales its backing store by
+        // `devicePixelRatio`, and the new size reaches `ScreenDimensions` on the next frame.
+        if let Some((width, height)) = canvas_size.take() {
+            window.set_inner_size(amethyst::winit::dpi::LogicalSize::new(width, height));
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use crate::{
+    audio::{
+        music_cue, AudioSettings, Decoded, Impact, Music, MusicDirector, Sounds, TrackStart,
+        WebAudio, WebTrack,
+    },
+    event::PongEvent,
+    game_config::GameConfig,
+    ScoreBoard,
+};
+use amethyst::{
+    assets::AssetStorage,
+    audio::Source,
+    core::timing::Time,
+    ecs::{Read, System, SystemData, World, Write},
+    shrev::{EventChannel, ReaderId},
+};
+
+/// This system plays sound effects and music through Web Audio, in place of `AudioSystem` and
+/// `MusicDirectorSystem` on the wasm build. It must run as a thread local system.
+#[derive(Default)]
+pub struct WebAudioSystem {
+    pong_event_reader: Option<ReaderId<PongEvent>>,
+    web_audio: Option<WebAudio>,
+    director: MusicDirector<WebTrack>,
+}
+
+impl<'s> System<'s> for WebAudioSystem {
+    type SystemData = (
+        Read<'s, AssetStorage<Source>>,
+        Option<Read<'s, Sounds>>,
+        Option<Write<'s, Music>>,
+        Read<'s, EventChannel<PongEvent>>,
+        Read<'s, AudioSettings>,
+        Read<'s, ScoreBoard>,
+        Read<'s, Time>,
+        Read<'s, GameConfig>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            storage,
+            sounds,
+            music,
+            pong_events,
+            audio_settings,
+            score_board,
+            time,
+            game_config,
+        ): Self::SystemData,
+    ) {
+        let reader = self
+            .pong_event_reader
+            .as_mut()
+            .expect("WebAudioSystem::setup has not been called");
+        let events = pong_events.read(reader).collect::<Vec<_>>();
+
+        let web_audio = match self.web_audio.as_ref() {
+            Some(web_audio) => web_audio,
+            None => return,
+        };
+
+        if let Some(sounds) = sounds {
+            // Every sound starts decoding as soon as it has loaded, rather than on its first
+            // event, which would then be silent. Sounds already decoded are only looked up.
+            for handle in sounds.all() {
+                web_audio.decoded(handle, &storage);
+            }
+
+            let volume = audio_settings.effects_volume();
+            for event in &events {
+                let buffer = match sounds
+                    .pick(event.kind())
+                    .map(|handle| web_audio.decoded(handle, &storage))
+                {
+                    Some(Decoded::Ready(buffer)) => buffer,
+                    // Sounds still decoding, such as right after the game starts, are skipped
+                    // rather than played late.
+                    _ => continue,
+                };
+                let impact = Impact::of(event, &game_config);
+                web_audio.play(&buffer, volume, impact);
+            }
+        }
+
+        if events
+            .iter()
+            .any(|event| matches!(event, PongEvent::Score { .. }))
+        {
+            self.director.duck();
+        }
+        if let Some(mut music) = music {
+            self.director.update(
+                &mut music,
+                music_cue(&score_board, &time),
+                audio_settings.music_volume(),
+                time.delta_real_seconds(),
+                |handle| match web_audio.decoded(handle, &storage) {
+                    Decoded::Ready(buffer) => web_audio.start_track(&buffer),
+                    Decoded::Decoding => TrackStart::Loading,
+                    Decoded::Failed => TrackStart::Failed,
+                },
+            );
+        }
+    }
+
+    fn setup(&mut self, world: &mut World) {
+        Self::SystemData::setup(world);
+        self.pong_event_reader = Some(
+            world
+                .fetch_mut::<EventChannel<PongEvent>>()
+                .register_reader(),
+        );
+        self.web_audio = WebAudio::new();
+    }
+}
@@ -1,84 +1,86 @@
-use crate::{event::PongEvent, Ball, ScoreBoard};
+use crate::{event::PongEvent, game_config::GameConfig, Ball, ScoreBoard, Side};
 use amethyst::{
-    core::Transform,
+    core::{timing::Time, Transform},
     derive::SystemDesc,
-    ecs::prelude::{Entity, Join, ReadExpect, System, SystemData, Write, WriteStorage},
+    ecs::prelude::{Entities, Join, Read, System, SystemData, Write, WriteStorage},
     shrev::EventChannel,
-    ui::UiText,
 };
 
 /// This system is responsible for checking if a ball has moved into a left or
 /// a right edge. Points are distributed to the player on the other side, and
-/// the ball is reset.
+/// the ball is reset. The scores are shown by the `ScoreUiSystem`.
 #[derive(SystemDesc)]
 pub struct WinnerSystem;
 
 impl<'s> System<'s> for WinnerSystem {
     type SystemData = (
+        Entities<'s>,
         WriteStorage<'s, Ball>,
         WriteStorage<'s, Transform>,
-        WriteStorage<'s, UiText>,
         Write<'s, ScoreBoard>,
         Write<'s, EventChannel<PongEvent>>,
-        ReadExpect<'s, ScoreText>,
+        Read<'s, GameConfig>,
+        Read<'s, Time>,
     );
 
     fn run(
         &mut self,
-        (
-            mut balls,
-            mut transforms,
-            mut text,
-            mut score_board,
-            mut pong_events,
-            score_text,
-        ): Self::SystemData,
+        (entities, mut balls, mut transforms, mut score_board, mut pong_events, game_config, time): Self::SystemData,
     ) {
-        for (ball, transform) in (&mut balls, &mut transforms).join() {
-            use crate::ARENA_WIDTH;
-
+        for (entity, ball, transform) in (&entities, &mut balls, &mut transforms).join() {
+            let arena_width = game_config.arena.width;
             let ball_x = transform.translation().x;
 
-            let did_hit = if ball_x <= ball.radius {
+            let scorer = if ball_x <= ball.radius {
                 // Right player scored on the left side.
                 // We top the score at 999 to avoid text overlap.
                 score_board.score_right = (score_board.score_right + 1).min(999);
-                if let Some(text) = text.get_mut(score_text.p2_score) {
-                    text.text = score_board.score_right.to_string();
-                }
-                true
-            } else if ball_x >= ARENA_WIDTH - ball.radius {
+                Some(Side::Right)
+            } else if ball_x >= arena_width - ball.radius {
                 // Left player scored on the right side.
                 // We top the score at 999 to avoid text overlap.
                 score_board.score_left = (score_board.score_left + 1).min(999);
-                if let Some(text) = text.get_mut(score_text.p1_score) {
-                    text.text = score_board.score_left.to_string();
-                }
-                true
+                Some(Side::Left)
             } else {
-                false
+                None
             };
 
-            if did_hit {
+            if let Some(side) = scorer {
+                let frame = time.frame_number();
+                // Where the ball went in, before it is put back in the centre.
+                let position = [ball_x, transform.translation().y];
+                let velocity = ball.velocity;
+
                 // Reset the ball.
                 ball.velocity[0] = -ball.velocity[0];
-                transform.set_translation_x(ARENA_WIDTH / 2.0);
+                transform.set_translation_x(arena_width / 2.0);
 
-                // Print the score board.
-                println!(
-                    "Score: | {:^3} | {:^3} |",
-                    score_board.score_left, score_board.score_right
-                );
+                // Writes an event for the audio and UI systems to pick up
+                pong_events.single_write(PongEvent::Score {
+                    ball: Some(entity),
+                    side,
+                    position,
+                    velocity,
+                    score_left: score_board.score_left,
+                    score_right: score_board.score_right,
+                    frame,
+                });
 
-                // Writes an event for the audio system to pick up
-                pong_events.single_write(PongEvent::Score)
+                match score_board.winner() {
+                    Some(winner) => pong_events.single_write(PongEvent::GameOver {
+                        winner,
+                        score_left: score_board.score_left,
+                        score_right: score_board.score_right,
+                        frame,
+                    }),
+                    None => pong_events.single_write(PongEvent::Serve {
+                        ball: Some(entity),
+                        position: [arena_width / 2.0, transform.translation().y],
+                        velocity: ball.velocity,
+                        frame,
+                    }),
+                }
             }
         }
     }
 }
-
-/// Stores the entities that are displaying the player score with UiText.
-pub struct ScoreText {
-    pub p1_score: Entity,
-    pub p2_score: Entity,
-}
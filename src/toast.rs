@@ -0,0 +1,24 @@
+use std::collections::VecDeque;
+
+/// Number of seconds each toast stays on screen.
+pub const TOAST_DURATION: f32 = 3.0;
+
+/// Short messages waiting to be shown at the bottom of the screen.
+#[derive(Debug, Default)]
+pub struct Toasts {
+    pending: VecDeque<String>,
+}
+
+impl Toasts {
+    /// Queues a message to be shown after the ones already queued.
+    pub fn push<S: Into<String>>(&mut self, message: S) {
+        let message = message.into();
+        log::info!("{}", message);
+        self.pending.push_back(message);
+    }
+
+    /// Returns the next message to show, if any.
+    pub fn pop(&mut self) -> Option<String> {
+        self.pending.pop_front()
+    }
+}
@@ -0,0 +1,191 @@
+//! Fitting the arena into the window.
+//!
+//! The arena keeps its aspect ratio and is centred in the window, with bars filling the rest.
+//! UI is laid out for an arena of `REFERENCE_SIZE` pixels and scaled with it.
+
+use amethyst::{
+    ecs::prelude::{Component, DenseVecStorage, Entity, World, WorldExt},
+    prelude::*,
+    ui::{Anchor, UiImage, UiTransform},
+};
+
+use crate::game_config::ArenaConfig;
+
+/// Height in pixels of the arena the UI is laid out for.
+pub const REFERENCE_SIZE: f32 = 500.0;
+
+const BAR_COLOUR: [f32; 4] = [0.2, 0.21, 0.31, 1.0];
+
+/// Where the arena is drawn in the window, in pixels.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Viewport {
+    /// Width of the bars on the left and right.
+    pub offset_x: f32,
+    /// Height of the bars on the top and bottom.
+    pub offset_y: f32,
+    pub width: f32,
+    pub height: f32,
+    /// Pixels per world unit.
+    pub scale: f32,
+}
+
+impl Viewport {
+    /// Returns the largest viewport with the aspect ratio of the arena that fits the screen.
+    pub fn fit(screen_width: f32, screen_height: f32, arena: &ArenaConfig) -> Self {
+        let scale = (screen_width / arena.width).min(screen_height / arena.height);
+        let width = arena.width * scale;
+        let height = arena.height * scale;
+        Viewport {
+            offset_x: (screen_width - width) * 0.5,
+            offset_y: (screen_height - height) * 0.5,
+            width,
+            height,
+            scale,
+        }
+    }
+
+    /// Returns how much UI laid out for `REFERENCE_SIZE` is scaled.
+    pub fn ui_scale(&self) -> f32 {
+        self.height / REFERENCE_SIZE
+    }
+}
+
+/// Layout of a UI element relative to the arena, as it would be in an arena of `REFERENCE_SIZE`
+/// pixels. The `UiTransform` and `UiText` of the entity are updated from it as the window
+/// resizes.
+pub struct ArenaUi {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    pub font_size: f32,
+    /// Screen size the element was last laid out for.
+    pub laid_out_for: Option<(f32, f32)>,
+}
+
+impl ArenaUi {
+    /// Returns the layout of `transform` and its text.
+    pub fn new(transform: &UiTransform, font_size: f32) -> Self {
+        ArenaUi {
+            x: transform.local_x,
+            y: transform.local_y,
+            width: transform.width,
+            height: transform.height,
+            font_size,
+            laid_out_for: None,
+        }
+    }
+}
+
+impl Component for ArenaUi {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Returns the offset of an element anchored at `anchor`, from the window edges to the arena.
+pub fn anchor_offset(anchor: &Anchor, viewport: &Viewport) -> (f32, f32) {
+    let x = match anchor {
+        Anchor::TopLeft | Anchor::MiddleLeft | Anchor::BottomLeft => viewport.offset_x,
+        Anchor::TopRight | Anchor::MiddleRight | Anchor::BottomRight => -viewport.offset_x,
+        Anchor::TopMiddle | Anchor::Middle | Anchor::BottomMiddle => 0.0,
+    };
+    let y = match anchor {
+        Anchor::TopLeft | Anchor::TopMiddle | Anchor::TopRight => -viewport.offset_y,
+        Anchor::BottomLeft | Anchor::BottomMiddle | Anchor::BottomRight => viewport.offset_y,
+        Anchor::MiddleLeft | Anchor::Middle | Anchor::MiddleRight => 0.0,
+    };
+    (x, y)
+}
+
+/// Bars covering the window outside the arena.
+pub struct Letterbox {
+    /// Left and right bars when the window is wider than the arena, top and bottom bars when it
+    /// is taller.
+    pub bars: [Entity; 2],
+}
+
+/// Creates the letterbox bars. Their size is set by the `ViewportSystem`.
+pub fn initialise_letterbox(world: &mut World) {
+    let mut create_bar = |id: &str| {
+        world
+            .create_entity()
+            .with(UiTransform::new(
+                id.to_string(),
+                Anchor::Middle,
+                Anchor::Middle,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+            ))
+            .with(UiImage::SolidColor(BAR_COLOUR))
+            .build()
+    };
+    let bars = [create_bar("letterbox_a"), create_bar("letterbox_b")];
+    world.insert(Letterbox { bars });
+}
+
+#[cfg(feature = "wasm")]
+pub use self::wasm::{listen_to_resize, CanvasSize};
+
+#[cfg(feature = "wasm")]
+mod wasm {
+    use std::sync::{Arc, Mutex};
+    use wasm_bindgen::{prelude::*, JsCast};
+    use web_sys::{Element, HtmlCanvasElement};
+
+    /// Size in CSS pixels the canvas should take, set when the page resizes.
+    #[derive(Clone, Default)]
+    pub struct CanvasSize {
+        pending: Arc<Mutex<Option<(f64, f64)>>>,
+    }
+
+    impl CanvasSize {
+        pub fn request(&self, size: (f64, f64)) {
+            if let Ok(mut pending) = self.pending.lock() {
+                *pending = Some(size);
+            }
+        }
+
+        /// Returns the size requested since the last call, if any.
+        pub fn take(&self) -> Option<(f64, f64)> {
+            self.pending
+                .lock()
+                .ok()
+                .and_then(|mut pending| pending.take())
+        }
+    }
+
+    /// Sizes the canvas to fill its parent element, now and whenever the page is resized or
+    /// zoomed.
+    pub fn listen_to_resize(
+        canvas: &HtmlCanvasElement,
+        canvas_size: &CanvasSize,
+    ) -> Result<(), JsValue> {
+        let window = web_sys::window().ok_or_else(|| JsValue::from_str("no window"))?;
+        let parent = match canvas.parent_element() {
+            Some(parent) => parent,
+            None => return Ok(()),
+        };
+
+        let fit_to_parent = |parent: &Element, canvas_size: &CanvasSize| {
+            let rect = parent.get_bounding_client_rect();
+            if rect.width() > 0.0 && rect.height() > 0.0 {
+                canvas_size.request((rect.width(), rect.height()));
+            }
+        };
+        fit_to_parent(&parent, canvas_size);
+
+        // Zooming changes `devicePixelRatio`, and also fires `resize`.
+        let on_resize = {
+            let canvas_size = canvas_size.clone();
+            Closure::wrap(Box::new(move || fit_to_parent(&parent, &canvas_size)) as Box<dyn FnMut()>)
+        };
+        window.add_event_listener_with_callback("resize", on_resize.as_ref().unchecked_ref())?;
+
+        // The listener lives as long as the page.
+        on_resize.forget();
+
+        Ok(())
+    }
+}